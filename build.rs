@@ -0,0 +1,29 @@
+use std::fs;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// stamps the commit and build time that /version reports
+fn main() {
+    // GIT_COMMIT from the environment wins, for builds from a source tarball without .git
+    let commit = std::env::var("GIT_COMMIT").ok().or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+        match output.status.success() {
+            true => Some(String::from_utf8(output.stdout).ok()?.trim().to_string()),
+            false => None
+        }
+    }).unwrap_or("unknown".to_string());
+    let built_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", built_at);
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    // only rebuilt when the checked out commit moves; a missing path would make cargo rerun this every build
+    if let Ok(head) = fs::read_to_string(".git/HEAD") {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Some(reference) = head.strip_prefix("ref: ") {
+            let reference = format!(".git/{}", reference.trim());
+            if fs::metadata(&reference).is_ok() {
+                println!("cargo:rerun-if-changed={}", reference);
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::{Json, Value};
+use rocket::{catch, Responder};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    pub name: String,
+    pub key: String,
+    // ops keys can read every client's usage
+    #[serde(default)]
+    pub ops: bool
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_minute: u32
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    #[serde(default = "default_rate_limit")]
+    pub rate_limit: RateLimit,
+    #[serde(default = "default_fan_out_rate_limit")]
+    pub fan_out_rate_limit: RateLimit
+}
+
+fn default_rate_limit() -> RateLimit {
+    return RateLimit { burst: 60, per_minute: 120 };
+}
+
+fn default_fan_out_rate_limit() -> RateLimit {
+    return RateLimit { burst: 3, per_minute: 6 };
+}
+
+// routes that turn one request into a dozen or so ESPN fetches draw from their own, smaller bucket
+const FAN_OUT_ROUTES: [&str; 6] = ["probable_lineup", "get_probable_lineups", "game_preview", "matchup", "lineup_widget", "scraper_health"];
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant
+}
+
+impl Bucket {
+    fn full(limit: RateLimit, now: Instant) -> Bucket {
+        return Bucket { tokens: limit.burst as f64, updated: now };
+    }
+
+    // takes a token, or says how long until the next one is available
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let per_second = limit.per_minute as f64 / 60.0;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(limit.burst as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        return Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second));
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default, ToSchema)]
+pub struct Usage {
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub routes: BTreeMap<String, u64>
+}
+
+struct ClientState {
+    bucket: Option<Bucket>,
+    fan_out_bucket: Option<Bucket>,
+    usage: Usage,
+    last_seen: Instant
+}
+
+impl ClientState {
+    fn new(now: Instant) -> ClientState {
+        return ClientState { bucket: None, fan_out_bucket: None, usage: Usage::default(), last_seen: now };
+    }
+}
+
+pub struct ApiKeys {
+    keys: HashMap<String, ApiKey>,
+    rate_limit: RateLimit,
+    fan_out_rate_limit: RateLimit,
+    // how long until an idle client's buckets are full again, after which forgetting it changes nothing
+    idle_after: Duration,
+    clients: Mutex<HashMap<String, ClientState>>
+}
+
+fn refill_time(limit: RateLimit) -> Duration {
+    return Duration::from_secs_f64(limit.burst as f64 * 60.0 / limit.per_minute as f64);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiClient {
+    pub name: String,
+    pub ops: bool
+}
+
+impl ApiKeys {
+    pub fn new(config: &ApiKeyConfig) -> Result<ApiKeys, String> {
+        for limit in [config.rate_limit, config.fan_out_rate_limit] {
+            if limit.burst == 0 || limit.per_minute == 0 {
+                return Err(format!("rate limits need a burst and per_minute of at least 1, got {:?}", limit));
+            }
+        }
+        return Ok(ApiKeys {
+            keys: config.api_keys.iter().map(|api_key| (api_key.key.to_string(), api_key.clone())).collect(),
+            rate_limit: config.rate_limit,
+            fan_out_rate_limit: config.fan_out_rate_limit,
+            idle_after: refill_time(config.rate_limit).max(refill_time(config.fan_out_rate_limit)),
+            clients: Mutex::new(HashMap::new())
+        });
+    }
+
+    // with no keys configured the API stays open and each client address gets its own buckets. nobody is ops then, so
+    // the admin routes need a configured ops key
+    fn authenticate(&self, key: Option<&str>, address: Option<String>) -> Option<ApiClient> {
+        if self.keys.is_empty() {
+            return Some(ApiClient { name: address.unwrap_or("unknown".to_string()), ops: false });
+        }
+        let api_key = self.keys.get(key?)?;
+        return Some(ApiClient { name: api_key.name.to_string(), ops: api_key.ops });
+    }
+
+    fn check(&self, client: &ApiClient, route: &str, now: Instant) -> Result<(), Duration> {
+        let mut clients = self.clients.lock().unwrap();
+        // open mode sees a new name for every address, so idle addresses are dropped along with their usage
+        if !self.required() && !clients.contains_key(&client.name) {
+            clients.retain(|_, state| now.saturating_duration_since(state.last_seen) < self.idle_after);
+        }
+        let state = clients.entry(client.name.to_string()).or_insert_with(|| ClientState::new(now));
+        state.last_seen = now;
+        let (bucket, limit) = match FAN_OUT_ROUTES.contains(&route) {
+            true => (&mut state.fan_out_bucket, self.fan_out_rate_limit),
+            false => (&mut state.bucket, self.rate_limit)
+        };
+        let result = bucket.get_or_insert_with(|| Bucket::full(limit, now)).take(limit, now);
+        match result {
+            Ok(()) => {
+                state.usage.requests += 1;
+                *state.usage.routes.entry(route.to_string()).or_insert(0) += 1;
+            }
+            Err(_) => state.usage.rate_limited += 1
+        }
+        return result;
+    }
+
+    // false in open mode, when no keys are configured
+    pub fn required(&self) -> bool {
+        return !self.keys.is_empty();
+    }
+
+    pub fn usage(&self) -> BTreeMap<String, Usage> {
+        return self.clients.lock().unwrap().iter().map(|(name, state)| (name.to_string(), state.usage.clone())).collect();
+    }
+}
+
+struct RetryAfter(u64);
+
+// the X-Api-Key header, or for widgets an `api_key` query parameter, since an iframe's src can't set headers. the
+// request log masks credential-looking query parameters, so the key doesn't end up in it
+fn key<'r>(request: &'r Request<'_>) -> Option<&'r str> {
+    if let Some(key) = request.headers().get_one("X-Api-Key") {
+        return Some(key);
+    }
+    return match request.uri().path().starts_with("/widgets/") {
+        true => request.query_value::<&str>("api_key").and_then(|key| key.ok()),
+        false => None
+    };
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiClient {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let api_keys = request.rocket().state::<ApiKeys>().unwrap();
+        let client = match api_keys.authenticate(key(request), request.client_ip().map(|ip| ip.to_string())) {
+            Some(client) => client,
+            None => return Outcome::Error((Status::Unauthorized, "missing or unknown API key".to_string()))
+        };
+        let route = request.route().and_then(|route| route.name.as_deref()).unwrap_or("unknown");
+        return match api_keys.check(&client, route, Instant::now()) {
+            Ok(()) => Outcome::Success(client),
+            Err(wait) => {
+                request.local_cache(|| RetryAfter(wait.as_secs_f64().ceil() as u64));
+                Outcome::Error((Status::TooManyRequests, format!("rate limit exceeded for {}", client.name)))
+            }
+        };
+    }
+}
+
+#[derive(Responder)]
+#[response(status = 429)]
+pub struct TooManyRequests {
+    body: Json<Value>,
+    retry_after: Header<'static>
+}
+
+#[catch(429)]
+pub fn too_many_requests(request: &Request) -> TooManyRequests {
+    let RetryAfter(seconds) = request.local_cache(|| RetryAfter(1));
+    return TooManyRequests {
+        body: Json(json!({"error": "rate limit exceeded", "retry_after": seconds})),
+        retry_after: Header::new("Retry-After", seconds.to_string())
+    };
+}
+
+#[test]
+fn bucket_test() {
+    let limit = RateLimit { burst: 2, per_minute: 60 };
+    let start = Instant::now();
+    let mut bucket = Bucket::full(limit, start);
+    assert_eq!(bucket.take(limit, start), Ok(()));
+    assert_eq!(bucket.take(limit, start), Ok(()));
+    assert_eq!(bucket.take(limit, start), Err(Duration::from_secs(1)));
+    assert_eq!(bucket.take(limit, start + Duration::from_secs(1)), Ok(()));
+    // an idle client only ever banks the burst
+    let later = start + Duration::from_secs(600);
+    assert_eq!(bucket.take(limit, later), Ok(()));
+    assert_eq!(bucket.take(limit, later), Ok(()));
+    assert_eq!(bucket.take(limit, later).is_err(), true);
+}
+
+#[test]
+fn open_mode_test() {
+    let config: ApiKeyConfig = serde_json::from_value(json!({"rate_limit": {"burst": 2, "per_minute": 60}})).unwrap();
+    let api_keys = ApiKeys::new(&config).unwrap();
+    let client = |address: &str| api_keys.authenticate(None, Some(address.to_string())).unwrap();
+    assert_eq!(client("10.0.0.1"), ApiClient { name: "10.0.0.1".to_string(), ops: false });
+
+    let start = Instant::now();
+    assert_eq!(api_keys.check(&client("10.0.0.1"), "teams", start), Ok(()));
+    assert_eq!(api_keys.check(&client("10.0.0.2"), "teams", start + Duration::from_secs(10)), Ok(()));
+    // the default fan out bucket takes the longest to refill, 30s, and only 10.0.0.1 has been idle that long
+    assert_eq!(api_keys.check(&client("10.0.0.3"), "teams", start + Duration::from_secs(35)), Ok(()));
+    assert_eq!(api_keys.usage().keys().cloned().collect::<Vec<String>>(), vec!["10.0.0.2", "10.0.0.3"]);
+}
+
+#[test]
+fn rate_limited_response_test() {
+    use rocket::local::blocking::Client;
+
+    let config: ApiKeyConfig = serde_json::from_value(json!({
+        "api_keys": [{"name": "partner", "key": "partner-key"}, {"name": "ops", "key": "ops-key", "ops": true}],
+        "rate_limit": {"burst": 1, "per_minute": 1}
+    })).unwrap();
+    let rocket = rocket::build()
+        .manage(ApiKeys::new(&config).unwrap())
+        .mount("/", rocket::routes![crate::api_usage])
+        .register("/", rocket::catchers![too_many_requests]);
+    let client = Client::tracked(rocket).unwrap();
+    let get = |key: &'static str| client.get("/admin/usage").header(Header::new("X-Api-Key", key)).dispatch();
+
+    assert_eq!(client.get("/admin/usage").dispatch().status(), Status::Unauthorized);
+    assert_eq!(get("wrong-key").status(), Status::Unauthorized);
+    assert_eq!(get("partner-key").status(), Status::Forbidden);
+    let limited = get("partner-key");
+    assert_eq!(limited.status(), Status::TooManyRequests);
+    assert_eq!(limited.headers().get_one("Retry-After"), Some("60"));
+
+    assert_eq!(client.get("/admin/usage?api_key=ops-key").dispatch().status(), Status::Unauthorized);
+    let usage = get("ops-key").into_json::<Value>().unwrap();
+    assert_eq!(usage["partner"]["requests"], 1);
+    assert_eq!(usage["partner"]["rate_limited"], 1);
+    assert_eq!(usage["ops"]["routes"]["api_usage"], 1);
+}
@@ -0,0 +1,232 @@
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::GameResult;
+use crate::league::League;
+use crate::odds::{parse_spread, parse_total};
+use crate::odds_history::OddsHistoryStore;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub enum Outcome {
+    #[serde(rename = "W")]
+    Win,
+    #[serde(rename = "L")]
+    Loss,
+    #[serde(rename = "P")]
+    Push
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub enum TotalOutcome {
+    #[serde(rename = "O")]
+    Over,
+    #[serde(rename = "U")]
+    Under,
+    #[serde(rename = "P")]
+    Push
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AtsGame {
+    game_id: String,
+    opponent: String,
+    at_vs: String,
+    team_score: u32,
+    opponent_score: u32,
+    spread: f64,
+    cover_margin: f64,
+    ats: Outcome,
+    total: u32,
+    over_under: f64,
+    total_vs_line: f64,
+    over_under_result: TotalOutcome
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct Record {
+    wins: u32,
+    losses: u32,
+    pushes: u32
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct OverUnderRecord {
+    overs: u32,
+    unders: u32,
+    pushes: u32
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AtsSummary {
+    games: usize,
+    ats: Record,
+    over_under: OverUnderRecord,
+    average_cover_margin: Option<f64>,
+    average_total: Option<f64>,
+    average_line: Option<f64>,
+    average_total_vs_line: Option<f64>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AtsReport {
+    team_code: String,
+    season: AtsSummary,
+    last_n: AtsSummary,
+    games: Vec<AtsGame>
+}
+
+// schedule scores are listed winner first, e.g. "125-102" for a loss by 23
+fn team_and_opponent_score(game_result: &GameResult) -> Option<(u32, u32)> {
+    let scores = game_result.score.split(|c: char| !c.is_ascii_digit())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    if scores.len() < 2 {
+        return None;
+    }
+    let (high, low) = (scores[0].max(scores[1]), scores[0].min(scores[1]));
+    return match game_result.result.as_ref() {
+        "W" => Some((high, low)),
+        "L" => Some((low, high)),
+        _ => None
+    };
+}
+
+fn ats_game(league: League, team_code: &str, game_result: &GameResult, store: &OddsHistoryStore) -> Option<AtsGame> {
+    let (team_score, opponent_score) = team_and_opponent_score(game_result)?;
+    // the last snapshot before tip-off is the closing line
+    let closing = store.snapshots(league, &game_result.game_id).pop()?.odds;
+    let spread = match team_code == closing.home_team {
+        true => parse_spread(&closing.home_spread)?,
+        false if team_code == closing.away_team => parse_spread(&closing.away_spread)?,
+        false => return None
+    };
+    let over_under = parse_total(&closing.over_under)?;
+    let cover_margin = team_score as f64 - opponent_score as f64 + spread;
+    let total = team_score + opponent_score;
+    let total_vs_line = total as f64 - over_under;
+    return Some(AtsGame {
+        game_id: game_result.game_id.to_string(),
+        opponent: game_result.opponent.to_string(),
+        at_vs: game_result.at_vs.to_string(),
+        team_score,
+        opponent_score,
+        spread,
+        cover_margin,
+        ats: match cover_margin {
+            m if m > 0.0 => Outcome::Win,
+            m if m < 0.0 => Outcome::Loss,
+            _ => Outcome::Push
+        },
+        total,
+        over_under,
+        total_vs_line,
+        over_under_result: match total_vs_line {
+            d if d > 0.0 => TotalOutcome::Over,
+            d if d < 0.0 => TotalOutcome::Under,
+            _ => TotalOutcome::Push
+        }
+    });
+}
+
+fn average(values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let average = values.iter().sum::<f64>() / values.len() as f64;
+    return Some((average * 100.0).round() / 100.0);
+}
+
+fn summarise(games: &[AtsGame]) -> AtsSummary {
+    let mut ats = Record::default();
+    let mut over_under = OverUnderRecord::default();
+    for game in games {
+        match game.ats {
+            Outcome::Win => ats.wins += 1,
+            Outcome::Loss => ats.losses += 1,
+            Outcome::Push => ats.pushes += 1
+        }
+        match game.over_under_result {
+            TotalOutcome::Over => over_under.overs += 1,
+            TotalOutcome::Under => over_under.unders += 1,
+            TotalOutcome::Push => over_under.pushes += 1
+        }
+    }
+    return AtsSummary {
+        games: games.len(),
+        ats,
+        over_under,
+        average_cover_margin: average(games.iter().map(|g| g.cover_margin).collect()),
+        average_total: average(games.iter().map(|g| g.total as f64).collect()),
+        average_line: average(games.iter().map(|g| g.over_under).collect()),
+        average_total_vs_line: average(games.iter().map(|g| g.total_vs_line).collect())
+    };
+}
+
+pub fn ats_report(league: League, team_code: &str, completed_games: &[GameResult], store: &OddsHistoryStore, last: usize) -> AtsReport {
+    let games = completed_games.iter()
+        .filter_map(|game_result| ats_game(league, team_code, game_result, store))
+        .collect::<Vec<AtsGame>>();
+    return AtsReport {
+        team_code: team_code.to_string(),
+        season: summarise(&games),
+        last_n: summarise(&games[..last.min(games.len())]),
+        games
+    };
+}
+
+#[rocket::async_test]
+async fn ats_report_test() {
+    use std::fs;
+    use chrono::Utc;
+    use crate::odds::game_odds_fixture;
+
+    let path = std::env::temp_dir().join("qr-ats-report-test.json");
+    let _ = fs::remove_file(&path);
+    let store = OddsHistoryStore::open(path.clone()).unwrap();
+    // Brooklyn are away in every game but the Nuggets one
+    let lines = [
+        ("401360612", "bkn", "uta", "+7.5", "-7.5", "220.5"),
+        ("401401134", "den", "bkn", "-7.5", "+7.5", "242.0"),
+        ("401360510", "bkn", "sa", "-3.0", "+3", "224.0"),
+        ("401360488", "bkn", "was", "-1.0", "+1", "230.0")
+    ];
+    for (game_id, away_team, home_team, away_spread, home_spread, over_under) in lines {
+        let odds = game_odds_fixture(away_team, home_team, away_spread, home_spread, "", "", over_under);
+        store.record(League::Nba, game_id, odds, Utc::now()).await;
+    }
+    let completed_games = crate::get_completed_games_from_team_page_html(&fs::read_to_string("./test-data/brooklyn-home-page-for-previous-games.html").unwrap());
+    let report = ats_report(League::Nba, "bkn", &completed_games, &store, 2);
+
+    assert_eq!(report.games.len(), 4);
+    assert_eq!(report.games[0].game_id, "401360612");
+    assert_eq!(report.games[0].team_score, 102);
+    assert_eq!(report.games[0].opponent_score, 125);
+    assert_eq!(report.games[0].spread, 7.5);
+    assert_eq!(report.games[0].cover_margin, -15.5);
+    assert_eq!(report.games[0].ats, Outcome::Loss);
+    assert_eq!(report.games[0].over_under_result, TotalOutcome::Over);
+    // at home the team's spread comes from the home side of the line
+    assert_eq!(report.games[1].game_id, "401401134");
+    assert_eq!(report.games[1].at_vs, "vs");
+    assert_eq!(report.games[1].spread, 7.5);
+    assert_eq!(report.games[1].cover_margin, 1.5);
+    assert_eq!(report.games[1].ats, Outcome::Win);
+    assert_eq!(report.games[1].total, 242);
+    assert_eq!(report.games[1].total_vs_line, 0.0);
+    assert_eq!(report.games[1].over_under_result, TotalOutcome::Push);
+    assert_eq!(report.games[2].ats, Outcome::Win);
+    assert_eq!(report.games[2].over_under_result, TotalOutcome::Under);
+    assert_eq!(report.games[3].ats, Outcome::Push);
+    assert_eq!(report.season.ats.wins, 2);
+    assert_eq!(report.season.ats.losses, 1);
+    assert_eq!(report.season.ats.pushes, 1);
+    assert_eq!(report.season.over_under.overs, 2);
+    assert_eq!(report.season.over_under.unders, 1);
+    assert_eq!(report.season.over_under.pushes, 1);
+    assert_eq!(report.last_n.games, 2);
+    assert_eq!(report.last_n.average_total, Some(234.5));
+    assert_eq!(report.last_n.average_total_vs_line, Some(3.25));
+    let _ = fs::remove_file(&path);
+}
+
@@ -0,0 +1,384 @@
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+
+use qr::league::League;
+use qr::preview;
+
+#[derive(Parser)]
+#[command(name = "qr-cli", about = "One-off ESPN scrapes and exports")]
+struct Cli {
+    #[arg(long, global = true, default_value = "nba", value_parser = parse_league)]
+    league: League,
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    #[arg(long, global = true, help = "Parse a saved HTML page instead of fetching it from ESPN")]
+    from_file: Option<PathBuf>,
+    #[arg(long, global = true, help = "Keep fetched pages in this directory, e.g. to capture new test-data fixtures")]
+    cache: Option<PathBuf>,
+    #[arg(long, global = true, help = "Save every fetched page as a fixture in this directory, e.g. test-data")]
+    record: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Box score of a team's latest game (--from-file takes a box score page)")]
+    Box { team: String },
+    #[command(about = "Matchup, venue, odds and season series for a game (--from-file takes a game page)")]
+    Game { game_id: String },
+    #[command(about = "Injury report, optionally for one team (--from-file takes the injuries page)")]
+    Injuries {
+        #[arg(long, help = "Team code or part of the team name")]
+        team: Option<String>
+    },
+    #[command(about = "Betting lines for a game (--from-file takes a game page)")]
+    Odds { game_id: String },
+    #[command(about = "Probable lineup by position (--from-file takes a box score page)")]
+    Lineup { team: String },
+    #[command(about = "Teams in the league (--from-file takes the teams page)")]
+    Teams
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv
+}
+
+// what every subcommand produces: the full payload for json, and flattened rows for table and csv
+struct Output {
+    json: Value,
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<String>>
+}
+
+fn parse_league(league: &str) -> Result<League, String> {
+    return League::from_path(league).ok_or(format!("unknown league: {}", league));
+}
+
+fn cell(value: &Value, pointer: &str) -> String {
+    return match value.pointer(pointer) {
+        Some(Value::String(text)) => text.to_string(),
+        Some(Value::Null) | None => "".to_string(),
+        Some(other) => other.to_string()
+    };
+}
+
+fn box_output(team_box: Value) -> Output {
+    let rows = team_box["player_records"].as_array().unwrap().iter().map(|record| {
+        let player = &record["player"];
+        vec![
+            cell(player, "/first_initial_and_last_name"),
+            cell(player, "/position"),
+            cell(player, "/starter"),
+            cell(player, "/minutes"),
+            format!("{}-{}", cell(player, "/field_goals_made"), cell(player, "/field_goals_attempted")),
+            format!("{}-{}", cell(player, "/three_point_field_goals_made"), cell(player, "/three_point_field_goals_attempted")),
+            format!("{}-{}", cell(player, "/free_throws_made"), cell(player, "/free_throws_attempted")),
+            cell(player, "/rebounds_total"),
+            cell(player, "/assists"),
+            cell(player, "/steals"),
+            cell(player, "/blocked_shots"),
+            cell(player, "/turnovers"),
+            cell(player, "/pf"),
+            cell(player, "/plus_minus"),
+            cell(player, "/points"),
+            cell(player, "/dnp")
+        ]
+    }).collect();
+    return Output {
+        json: team_box,
+        columns: vec!["player", "pos", "starter", "min", "fg", "3pt", "ft", "reb", "ast", "stl", "blk", "to", "pf", "+/-", "pts", "dnp"],
+        rows
+    };
+}
+
+fn odds_row(odds: &Value) -> Vec<String> {
+    return vec![
+        cell(odds, "/away_team"),
+        cell(odds, "/home_team"),
+        cell(odds, "/away_spread"),
+        cell(odds, "/home_spread"),
+        cell(odds, "/away_moneyline"),
+        cell(odds, "/home_moneyline"),
+        cell(odds, "/over_under")
+    ];
+}
+
+fn odds_output(odds: Value) -> Output {
+    let rows = match odds.is_null() {
+        true => vec![],
+        false => vec![odds_row(&odds)]
+    };
+    return Output {
+        json: odds,
+        columns: vec!["away", "home", "away_spread", "home_spread", "away_moneyline", "home_moneyline", "over_under"],
+        rows
+    };
+}
+
+fn game_output(game: Value) -> Output {
+    let mut row = vec![
+        cell(&game, "/game/game_id"),
+        format!("{} ({})", cell(&game, "/game/away_team/team_code"), cell(&game, "/game/away_team/record")),
+        format!("{} ({})", cell(&game, "/game/home_team/team_code"), cell(&game, "/game/home_team/record")),
+        cell(&game, "/game/tip_off"),
+        cell(&game, "/game/venue"),
+        cell(&game, "/game/tv")
+    ];
+    let odds = &game["odds"];
+    row.push(cell(odds, "/home_spread"));
+    row.push(cell(odds, "/over_under"));
+    row.push(game["head_to_head"].as_array().map(|games| games.len()).unwrap_or(0).to_string());
+    return Output {
+        json: game,
+        columns: vec!["game_id", "away", "home", "tip_off", "venue", "tv", "home_spread", "over_under", "season_series_games"],
+        rows: vec![row]
+    };
+}
+
+fn matches_team(report: &Value, team: &str) -> bool {
+    let team = team.to_lowercase();
+    return cell(report, "/team_code").to_lowercase() == team || cell(report, "/team_name").to_lowercase().contains(&team);
+}
+
+fn injuries_output(team_injury_reports: Value, team: Option<String>) -> Output {
+    let reports = team_injury_reports.as_array().unwrap().iter()
+        .filter(|report| team.as_ref().map(|team| matches_team(report, team)).unwrap_or(true))
+        .cloned()
+        .collect::<Vec<Value>>();
+    let mut rows = Vec::new();
+    for report in &reports {
+        for injury in report["injuries"].as_array().unwrap() {
+            rows.push(vec![
+                cell(report, "/team_code"),
+                cell(report, "/team_name"),
+                cell(injury, "/name"),
+                cell(injury, "/position"),
+                cell(injury, "/status"),
+                cell(injury, "/date"),
+                cell(injury, "/description")
+            ]);
+        }
+    }
+    return Output {
+        json: Value::Array(reports),
+        columns: vec!["team", "team_name", "player", "pos", "status", "date", "description"],
+        rows
+    };
+}
+
+fn lineup_output(lineup: Value) -> Output {
+    let lineup_by_position = match lineup.get("lineup_by_position") {
+        Some(lineup_by_position) => lineup_by_position,
+        None => &lineup
+    };
+    let rows = vec!["PG", "SG", "SF", "PF", "C"].into_iter()
+        .filter_map(|position| {
+            let players = lineup_by_position[position].as_array()?;
+            let names = players.iter().map(|player| cell(player, "/player/first_initial_and_last_name")).collect::<Vec<String>>();
+            Some(vec![position.to_string(), names.first().cloned().unwrap_or_default(), names.iter().skip(1).cloned().collect::<Vec<String>>().join(", ")])
+        })
+        .collect();
+    return Output {
+        json: lineup,
+        columns: vec!["pos", "starter", "bench"],
+        rows
+    };
+}
+
+fn teams_output(teams: Value) -> Output {
+    let rows = teams.as_array().unwrap().iter()
+        .map(|team| vec![cell(team, "/id"), cell(team, "/full_name")])
+        .collect();
+    return Output {
+        json: teams,
+        columns: vec!["id", "full_name"],
+        rows
+    };
+}
+
+// Err with a message for stderr when ESPN can't be reached or answers with an error page
+async fn fetch_page(url: String) -> Result<String, String> {
+    let response = qr::upstream::get(url.to_string()).await.map_err(|error| format!("could not fetch {}: {}", url, error))?;
+    if !response.status().is_success() {
+        return Err(format!("ESPN answered {} for {}", response.status(), url));
+    }
+    return response.text().await.map_err(|error| format!("could not read {}: {}", url, error));
+}
+
+fn status_error(what: &str, status: rocket::http::Status) -> String {
+    return match status.code {
+        404 => format!("{}: not found on ESPN", what),
+        _ => format!("{}: ESPN failed ({})", what, status)
+    };
+}
+
+async fn run(cli: Cli) -> Result<Output, String> {
+    let league = cli.league;
+    let saved_page = match cli.from_file {
+        Some(path) => Some(fs::read_to_string(&path).map_err(|error| format!("could not read {}: {}", path.display(), error))?),
+        None => None
+    };
+    return Ok(match cli.command {
+        Command::Box { team } => {
+            let team_box = match saved_page {
+                Some(html) => qr::get_orientation(&html, &team).and_then(|home_or_away| qr::find_latest_game_box(&html, home_or_away, league)).ok_or(format!("no box score for {} in the saved page", team))?,
+                None => qr::get_team_box_score(league, &team).await.map_err(|status| status_error(&format!("no box score for {}", team), status))?
+            };
+            box_output(serde_json::to_value(team_box).unwrap())
+        }
+        Command::Game { game_id } => {
+            let html = match saved_page {
+                Some(html) => html,
+                None => fetch_page(league.url(&format!("game/_/gameId/{}", game_id))).await?
+            };
+            let game = preview::get_game_info_from_html(&game_id, &html).ok_or(format!("no game info for {}", game_id))?;
+            game_output(json!({
+                "game": game,
+                "head_to_head": preview::get_season_series_from_html(&html),
+                "odds": qr::get_odds_for_game_html(html)
+            }))
+        }
+        Command::Injuries { team } => {
+            // a saved page has no team codes; --team still matches on the team name
+            let team_injury_reports = match saved_page {
+                Some(html) => qr::injuries(html),
+                None => qr::get_injuries_with_team_code(league).await.map_err(|status| status_error("no injuries", status))?
+            };
+            injuries_output(serde_json::to_value(team_injury_reports).unwrap(), team)
+        }
+        Command::Odds { game_id } => {
+            let odds = match saved_page {
+                Some(html) => qr::get_odds_for_game_html(html),
+                None => qr::find_odds_for_game(league, game_id.to_string()).await.map_err(|status| status_error(&format!("no odds for {}", game_id), status))?
+            };
+            if odds.is_none() {
+                eprintln!("no odds posted for game {}", game_id);
+            }
+            odds_output(serde_json::to_value(odds).unwrap())
+        }
+        Command::Lineup { team } => {
+            let lineup = match saved_page {
+                Some(html) => serde_json::to_value(qr::get_probable_lineup_from_box_score_html(&html, &team, league).ok_or(format!("no box score for {} in the saved page", team))?).unwrap(),
+                None => {
+                    let injuries = qr::get_injuries_with_team_code(league).await.map_err(|status| status_error("no injuries", status))?;
+                    let lineup = qr::get_probable_lineup(league, &team, &injuries).await.map_err(|status| status_error(&format!("no lineup for {}", team), status))?;
+                    serde_json::to_value(lineup).unwrap()
+                }
+            };
+            lineup_output(lineup)
+        }
+        Command::Teams => {
+            let teams = match saved_page {
+                Some(html) => qr::get_teams(html, league),
+                None => qr::fetch_teams(league).await.map_err(|status| status_error("no teams", status))?
+            };
+            teams_output(serde_json::to_value(teams).unwrap())
+        }
+    });
+}
+
+fn render_table(columns: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = columns.iter().map(|column| column.chars().count()).collect::<Vec<usize>>();
+    for row in rows {
+        for (i, value) in row.iter().enumerate() {
+            widths[i] = widths[i].max(value.chars().count());
+        }
+    }
+    let line = |values: Vec<String>| values.iter().enumerate()
+        .map(|(i, value)| format!("{:width$}", value, width = widths[i]))
+        .collect::<Vec<String>>()
+        .join("  ")
+        .trim_end()
+        .to_string();
+    let mut lines = vec![
+        line(columns.iter().map(|column| column.to_string()).collect()),
+        line(widths.iter().map(|width| "-".repeat(*width)).collect())
+    ];
+    for row in rows {
+        lines.push(line(row.clone()));
+    }
+    return lines.join("\n");
+}
+
+fn write_csv(columns: &[&str], rows: &[Vec<String>]) {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+    writer.write_record(columns).unwrap();
+    for row in rows {
+        writer.write_record(row).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+fn exit_with(error: &str) -> ! {
+    eprintln!("qr-cli: {}", error);
+    std::process::exit(1);
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    // retries and stale pages are worth seeing on stderr; spans and request logs are noise in a one-off run
+    let _ = qr::logging::init(&qr::logging::LoggingConfig { log_filter: "warn".to_string(), log_format: qr::logging::LogFormat::Text });
+    if let Some(dir) = &cli.cache {
+        qr::page_cache::configure(qr::page_cache::PageCache::open(dir.clone()).unwrap_or_else(|error| exit_with(&error)));
+    }
+    if let Some(dir) = &cli.record {
+        qr::fixtures::configure(qr::fixtures::FixtureRecorder::open(dir.clone()).unwrap_or_else(|error| exit_with(&error)));
+    }
+    let format = cli.format;
+    let output = run(cli).await.unwrap_or_else(|error| exit_with(&error));
+    match format {
+        Format::Table => println!("{}", render_table(&output.columns, &output.rows)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&output.json).unwrap()),
+        Format::Csv => write_csv(&output.columns, &output.rows)
+    }
+}
+
+#[test]
+fn teams_table_test() {
+    let teams = qr::get_teams(fs::read_to_string("./test-data/teams-page.html").unwrap(), League::Nba);
+    let output = teams_output(serde_json::to_value(teams).unwrap());
+    assert_eq!(output.rows.len(), 30);
+    let table = render_table(&output.columns, &output.rows);
+    let lines = table.lines().collect::<Vec<&str>>();
+    assert_eq!(lines[0], "id    full_name");
+    assert_eq!(lines[1].starts_with("----  ---------"), true);
+    assert_eq!(lines.len(), 32);
+}
+
+#[test]
+fn box_and_lineup_rows_test() {
+    let html = fs::read_to_string("./test-data/raptors-home-box.html").unwrap();
+    let team_box = qr::find_latest_game_box(&html, qr::get_orientation(&html, "tor").unwrap(), League::Nba).unwrap();
+    let output = box_output(serde_json::to_value(team_box).unwrap());
+    assert_eq!(output.columns.len(), output.rows[0].len());
+    assert_eq!(output.rows[0][2], "true");
+    let lineup = lineup_output(serde_json::to_value(qr::get_probable_lineup_from_box_score_html(&html, "tor", League::Nba).unwrap()).unwrap());
+    assert_eq!(lineup.rows.iter().map(|row| row[0].as_str()).collect::<Vec<&str>>(), vec!["PG", "SG", "SF", "PF", "C"]);
+}
+
+#[test]
+fn injuries_filter_test() {
+    let team_injury_reports = qr::injuries(fs::read_to_string("./test-data/injuries.html").unwrap());
+    let output = injuries_output(serde_json::to_value(team_injury_reports).unwrap(), Some("hawks".to_string()));
+    assert_eq!(output.json.as_array().unwrap().len(), 1);
+    assert_eq!(output.rows.iter().all(|row| row[1] == "Atlanta Hawks"), true);
+}
+
+#[tokio::test]
+async fn saved_page_errors_test() {
+    let cli = Cli::parse_from(["qr-cli", "--from-file", "./test-data/teams-page.html", "game", "401360432"]);
+    assert_eq!(run(cli).await.err(), Some("no game info for 401360432".to_string()));
+    let cli = Cli::parse_from(["qr-cli", "--from-file", "./test-data/missing.html", "teams"]);
+    assert_eq!(run(cli).await.err().unwrap().starts_with("could not read ./test-data/missing.html"), true);
+}
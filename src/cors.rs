@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use rocket_cors::{AllowedHeaders, AllowedOrigins, Cors, CorsOptions, Method};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorsConfig {
+    #[serde(default = "default_cors_allowed_origins")]
+    pub cors_allowed_origins: Vec<String>,
+    #[serde(default = "default_cors_allowed_methods")]
+    pub cors_allowed_methods: Vec<String>,
+    #[serde(default = "default_cors_allowed_headers")]
+    pub cors_allowed_headers: Vec<String>,
+    #[serde(default = "default_cors_max_age")]
+    pub cors_max_age: Option<usize>,
+    #[serde(default)]
+    pub cors_allow_credentials: bool
+}
+
+fn default_cors_allowed_origins() -> Vec<String> {
+    return vec!["*".to_string()];
+}
+
+fn default_cors_allowed_methods() -> Vec<String> {
+    return vec!["GET".to_string(), "POST".to_string()];
+}
+
+fn default_cors_allowed_headers() -> Vec<String> {
+    return vec!["Accept".to_string(), "Content-Type".to_string(), "X-Api-Key".to_string()];
+}
+
+fn default_cors_max_age() -> Option<usize> {
+    return Some(24 * 60 * 60);
+}
+
+// the fairing answers the OPTIONS preflight for every mounted route, so no route needs its own
+pub fn fairing(config: &CorsConfig) -> Result<Cors, String> {
+    let any_origin = config.cors_allowed_origins.iter().any(|origin| origin == "*");
+    // browsers refuse credentials alongside a wildcard, and echoing back any origin instead would hand cookies to every site
+    if any_origin && config.cors_allow_credentials {
+        return Err("cors_allow_credentials needs an explicit cors_allowed_origins list".to_string());
+    }
+    let allowed_origins = match any_origin {
+        true => AllowedOrigins::all(),
+        false => AllowedOrigins::some_exact(&config.cors_allowed_origins)
+    };
+    let mut allowed_methods = HashSet::new();
+    for method in &config.cors_allowed_methods {
+        allowed_methods.insert(Method::from_str(&method.to_uppercase()).map_err(|_| format!("unknown method in cors_allowed_methods: {}", method))?);
+    }
+    let allowed_headers = match config.cors_allowed_headers.iter().any(|header| header == "*") {
+        true => AllowedHeaders::all(),
+        false => AllowedHeaders::some(&config.cors_allowed_headers.iter().map(|header| header.as_str()).collect::<Vec<&str>>())
+    };
+    let options = CorsOptions {
+        allowed_origins,
+        allowed_methods,
+        allowed_headers,
+        allow_credentials: config.cors_allow_credentials,
+        max_age: config.cors_max_age,
+        // a plain `*` keeps responses cacheable across origins when credentials are off
+        send_wildcard: any_origin,
+        ..Default::default()
+    };
+    return options.to_cors().map_err(|error| error.to_string());
+}
+
+#[cfg(test)]
+fn test_config() -> CorsConfig {
+    return serde_json::from_value(json!({})).unwrap();
+}
+
+#[test]
+fn credentials_need_explicit_origins_test() {
+    let mut config = test_config();
+    config.cors_allow_credentials = true;
+    assert_eq!(fairing(&config).is_err(), true);
+    config.cors_allowed_origins = vec!["https://example.com".to_string()];
+    assert_eq!(fairing(&config).is_ok(), true);
+    config.cors_allowed_methods = vec!["FETCH".to_string()];
+    assert_eq!(fairing(&config).is_err(), true);
+}
+
+#[test]
+fn preflight_test() {
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    let mut config = test_config();
+    config.cors_allowed_origins = vec!["https://example.com".to_string()];
+    config.cors_allow_credentials = true;
+    let client = Client::tracked(rocket::build().mount("/v1", rocket::routes![crate::v1::teams]).attach(fairing(&config).unwrap())).unwrap();
+    let preflight = |origin: &'static str| client.options("/v1/nba/teams")
+        .header(Header::new("Origin", origin))
+        .header(Header::new("Access-Control-Request-Method", "GET"))
+        .dispatch();
+
+    let response = preflight("https://example.com");
+    assert_eq!(response.status(), Status::NoContent);
+    assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("https://example.com"));
+    assert_eq!(response.headers().get_one("Access-Control-Allow-Credentials"), Some("true"));
+    assert_eq!(response.headers().get_one("Access-Control-Max-Age"), Some("86400"));
+    assert_eq!(preflight("https://elsewhere.com").status(), Status::Forbidden);
+}
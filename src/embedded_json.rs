@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{GameOdds, GameResult, GameScore, HomeOrAway, Logos, OrientedTeam, Overview, Player, PlayerBoxScore, Team, TeamBox, TeamScore, TwoTeams, get_script_assignment};
+use crate::league::League;
+use crate::odds::{self, MatchupPredictor, ProviderLine};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParserBackend {
+    Html,
+    Json
+}
+
+impl Default for ParserBackend {
+    fn default() -> Self {
+        return ParserBackend::Html;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PageType {
+    Teams,
+    TeamPage,
+    BoxScore,
+    Game
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ParserBackends {
+    #[serde(default)]
+    pub teams: ParserBackend,
+    #[serde(default)]
+    pub team_page: ParserBackend,
+    #[serde(default)]
+    pub box_score: ParserBackend,
+    #[serde(default)]
+    pub game: ParserBackend
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParserBackendConfig {
+    #[serde(default)]
+    pub parser_backends: ParserBackends
+}
+
+fn configured() -> &'static OnceLock<ParserBackends> {
+    static BACKENDS: OnceLock<ParserBackends> = OnceLock::new();
+    return &BACKENDS;
+}
+
+pub fn configure(backends: ParserBackends) {
+    let _ = configured().set(backends);
+}
+
+fn backend(page: PageType) -> ParserBackend {
+    let backends = match configured().get() {
+        Some(backends) => backends,
+        None => return ParserBackend::Html
+    };
+    return match page {
+        PageType::Teams => backends.teams,
+        PageType::TeamPage => backends.team_page,
+        PageType::BoxScore => backends.box_score,
+        PageType::Game => backends.game
+    };
+}
+
+// the state blob the newer ESPN pages hydrate from, assigned as `window['__espnfitt__']={...};`
+pub fn get_espnfitt(html: &str) -> Option<Value> {
+    let start = html.find("window['__espnfitt__']")?;
+    let json_start = start + html[start..].find('=')? + 1;
+    let json_end = json_start + html[json_start..].find("</script>")?;
+    return serde_json::from_str(html[json_start..json_end].trim().trim_end_matches(';')).ok();
+}
+
+// game and box score pages still carry the summary feed as a script variable instead
+pub fn get_gamepackage_data(html: &str) -> Option<Value> {
+    return serde_json::from_str(get_script_assignment(html, "espn.gamepackage.data")?).ok();
+}
+
+fn page_data(page: PageType, html: &str) -> Option<Value> {
+    return match page {
+        PageType::Teams | PageType::TeamPage => get_espnfitt(html).map(|blob| blob["page"]["content"].clone()),
+        PageType::BoxScore | PageType::Game => get_gamepackage_data(html)
+    };
+}
+
+fn parse_with<T>(backend: ParserBackend, page: PageType, html: &str, parser: impl FnOnce(&Value) -> Option<T>) -> Option<T> {
+    if backend != ParserBackend::Json {
+        return None;
+    }
+    let parsed = page_data(page, html).and_then(|data| parser(&data));
+    if parsed.is_none() {
+        tracing::warn!(page = ?page, "embedded_json_fallback");
+    }
+    return parsed;
+}
+
+// None when the page type is configured for HTML or the blob is missing, so callers fall back to the CSS parsers
+pub fn parse<T>(page: PageType, html: &str, parser: impl FnOnce(&Value) -> Option<T>) -> Option<T> {
+    return parse_with(backend(page), page, html, parser);
+}
+
+fn text(value: &Value) -> String {
+    return value.as_str().unwrap_or("").to_string();
+}
+
+pub fn teams(content: &Value, league: League) -> Option<Vec<Team>> {
+    let mut teams = Vec::new();
+    for division in content["teams"][league.path()].as_array()? {
+        for team in division["teams"].as_array()? {
+            teams.push(Team {
+                id: team["abbrev"].as_str()?.to_lowercase(),
+                full_name: text(&team["name"])
+            });
+        }
+    }
+    return match teams.is_empty() {
+        true => None,
+        false => Some(teams)
+    };
+}
+
+// the team page's schedule strip, upcoming games first and then the completed ones, newest first
+fn schedule(content: &Value) -> Option<Vec<&Value>> {
+    let mut games = Vec::new();
+    for season in content["clubhouse"]["columns"]["leftColumn"]["schedule"]["seasons"].as_array()? {
+        games.extend(season["feed"].as_array()?);
+    }
+    return Some(games);
+}
+
+fn first_with_status<'a>(games: &[&'a Value], status: &str) -> Option<&'a Value> {
+    return games.iter().find(|game| game["status"].as_str() == Some(status)).copied();
+}
+
+pub fn completed_games(content: &Value) -> Option<Vec<GameResult>> {
+    let mut game_results = Vec::new();
+    for game in schedule(content)? {
+        // postponed games are marked post with "PPD" in place of "<result> <score>"
+        let (result, score) = match (game["status"].as_str(), game["score"].as_str().and_then(|score| score.split_once(' '))) {
+            (Some("post"), Some(result_and_score)) => result_and_score,
+            _ => continue
+        };
+        game_results.push(GameResult {
+            game_id: text(&game["id"]),
+            opponent: text(&game["opponentName"]),
+            score: score.to_string(),
+            at_vs: text(&game["atVs"]),
+            result: result.to_string(),
+            box_score_link: text(&game["link"]).replace("/game/", "/boxscore/")
+        });
+    }
+    return Some(game_results);
+}
+
+// a game in progress wins over the last completed one, so the box score follows along
+pub fn latest_game_id(content: &Value) -> Option<String> {
+    let games = schedule(content)?;
+    let game = first_with_status(&games, "in").or_else(|| first_with_status(&games, "post"))?;
+    return game["id"].as_str().map(|id| id.to_string());
+}
+
+pub fn upcoming_game_id(content: &Value) -> Option<String> {
+    return first_with_status(&schedule(content)?, "pre")?["id"].as_str().map(|id| id.to_string());
+}
+
+// the short name, e.g. "Hawks", which callers map to a team code the same way as the name in the markup
+pub fn upcoming_opponent_name(content: &Value) -> Option<String> {
+    return first_with_status(&schedule(content)?, "pre")?["opponentName"].as_str().map(|name| name.to_string());
+}
+
+fn competitor<'a>(data: &'a Value, home_or_away: &HomeOrAway) -> Option<&'a Value> {
+    return data["header"]["competitions"][0]["competitors"].as_array()?.iter()
+        .find(|competitor| competitor["homeAway"].as_str() == Some(&home_or_away.to_string()));
+}
+
+fn team_code(competitor: &Value) -> String {
+    return text(&competitor["team"]["abbreviation"]).to_lowercase();
+}
+
+fn oriented_team(competitor: &Value) -> OrientedTeam {
+    return OrientedTeam {
+        logos: Logos { w72xh72: text(&competitor["team"]["logos"][0]["href"]) },
+        id: team_code(competitor),
+        medium_name: text(&competitor["team"]["name"])
+    };
+}
+
+pub fn orientation(data: &Value, team_code_to_find: &str) -> Option<HomeOrAway> {
+    let away = competitor(data, &HomeOrAway::away)?;
+    return Some(match team_code(away) == team_code_to_find {
+        true => HomeOrAway::away,
+        false => HomeOrAway::home
+    });
+}
+
+fn overview(data: &Value, league: League) -> Option<Overview> {
+    let home = competitor(data, &HomeOrAway::home)?;
+    let away = competitor(data, &HomeOrAway::away)?;
+    return Some(Overview {
+        share_url: league.url(&format!("boxscore/_/gameId/{}", text(&data["header"]["id"]))),
+        score: GameScore {
+            away: TeamScore { score: text(&away["score"]) },
+            home: TeamScore { score: text(&home["score"]) }
+        },
+        event: TwoTeams {
+            away_team: oriented_team(away),
+            home_team: oriented_team(home)
+        }
+    });
+}
+
+fn made_and_attempted(value: &str) -> (String, String) {
+    let (made, attempted) = value.split_once('-').unwrap_or((value, ""));
+    return (made.to_string(), attempted.to_string());
+}
+
+fn player_box_score(athlete: &Value, labels: &[Value], league: League) -> PlayerBoxScore {
+    let player_id = text(&athlete["athlete"]["id"]);
+    let mut headshots = HashMap::new();
+    headshots.insert("w192xh192".to_string(), league.headshot_url(&player_id));
+    let mut player = PlayerBoxScore {
+        starter: athlete["starter"].as_bool().unwrap_or(false),
+        first_initial_and_last_name: text(&athlete["athlete"]["shortName"]),
+        player_id,
+        position: text(&athlete["athlete"]["position"]["abbreviation"]),
+        minutes: "".to_string(),
+        field_goals_made: "".to_string(),
+        field_goals_attempted: "".to_string(),
+        three_point_field_goals_made: "".to_string(),
+        three_point_field_goals_attempted: "".to_string(),
+        free_throws_made: "".to_string(),
+        free_throws_attempted: "".to_string(),
+        oreb: "".to_string(),
+        dreb: "".to_string(),
+        rebounds_total: "".to_string(),
+        assists: "".to_string(),
+        steals: "".to_string(),
+        blocked_shots: "".to_string(),
+        turnovers: "".to_string(),
+        pf: "".to_string(),
+        plus_minus: "".to_string(),
+        points: "".to_string(),
+        dnp: match athlete["didNotPlay"].as_bool().unwrap_or(false) {
+            true => text(&athlete["reason"]),
+            false => "".to_string()
+        },
+        headshots
+    };
+    let stats = athlete["stats"].as_array().cloned().unwrap_or_default();
+    for (label, value) in labels.iter().zip(stats.iter()) {
+        let value = text(value);
+        match label.as_str().unwrap_or("") {
+            "MIN" => player.minutes = value,
+            "FG" => (player.field_goals_made, player.field_goals_attempted) = made_and_attempted(&value),
+            "3PT" => (player.three_point_field_goals_made, player.three_point_field_goals_attempted) = made_and_attempted(&value),
+            "FT" => (player.free_throws_made, player.free_throws_attempted) = made_and_attempted(&value),
+            "OREB" => player.oreb = value,
+            "DREB" => player.dreb = value,
+            "REB" => player.rebounds_total = value,
+            "AST" => player.assists = value,
+            "STL" => player.steals = value,
+            "BLK" => player.blocked_shots = value,
+            "TO" => player.turnovers = value,
+            "PF" => player.pf = value,
+            "+/-" => player.plus_minus = value,
+            "PTS" => player.points = value,
+            _ => ()
+        }
+    }
+    return player;
+}
+
+pub fn team_box(data: &Value, home_or_away: &HomeOrAway, league: League) -> Option<TeamBox> {
+    let team_id = competitor(data, home_or_away)?["id"].as_str()?.to_string();
+    let players = data["boxscore"]["players"].as_array()?.iter()
+        .find(|players| players["team"]["id"].as_str() == Some(&team_id))?;
+    let mut player_records = Vec::new();
+    for statistics in players["statistics"].as_array()? {
+        let labels = statistics["names"].as_array().cloned().unwrap_or_default();
+        for athlete in statistics["athletes"].as_array()? {
+            let player = player_box_score(athlete, &labels, league);
+            player_records.push(Player {
+                id: player.player_id.to_string(),
+                alignment: home_or_away.to_string(),
+                player
+            });
+        }
+    }
+    let mut manager = HashMap::new();
+    manager.insert("image".to_string(), "https://i.imgur.com/QkbchIz.jpg".to_string());
+    manager.insert("name".to_string(), "Nick Nurse".to_string());
+    return Some(TeamBox {
+        overview: overview(data, league)?,
+        player_records,
+        manager
+    });
+}
+
+fn signed(value: Option<f64>) -> String {
+    return value.map(|value| format!("{:+}", value)).unwrap_or_default();
+}
+
+// the game page shows the favourite's spread as written in `details`, e.g. "-4.0" from "PHX -4.0", and the underdog's
+// as a bare signed number, e.g. "+4"; matching it keeps both backends' strings, and so the odds history, identical
+fn spread_text(line: &Value, team_code: &str, spread: Option<f64>) -> String {
+    return match line["details"].as_str().and_then(|details| details.split_once(' ')) {
+        Some((abbreviation, value)) if abbreviation.eq_ignore_ascii_case(team_code) && value.parse::<f64>().is_ok() => value.to_string(),
+        _ => signed(spread)
+    };
+}
+
+fn matchup_predictor(predictor: &Value) -> Option<MatchupPredictor> {
+    let percentage = |value: &Value| value.as_str().and_then(|value| value.parse::<f64>().ok());
+    // ESPN often only fills in the away side; its chance of losing is the home side's chance of winning
+    let away_win_percentage = percentage(&predictor["awayTeam"]["gameProjection"])?;
+    let home_win_percentage = percentage(&predictor["homeTeam"]["gameProjection"])
+        .or_else(|| percentage(&predictor["awayTeam"]["teamChanceLoss"]))?;
+    return Some(MatchupPredictor { home_win_percentage, away_win_percentage });
+}
+
+pub fn game_odds(data: &Value) -> Option<GameOdds> {
+    let pickcenter = data["pickcenter"].as_array()?;
+    let line = pickcenter.first()?;
+    let home_spread = line["spread"].as_f64();
+    let home_team = team_code(competitor(data, &HomeOrAway::home)?);
+    let away_team = team_code(competitor(data, &HomeOrAway::away)?);
+    let providers = pickcenter.iter().filter_map(odds::provider_line).collect::<Vec<ProviderLine>>();
+    let mut game_odds = GameOdds {
+        home_spread: spread_text(line, &home_team, home_spread),
+        away_spread: spread_text(line, &away_team, home_spread.map(|spread| -spread)),
+        home_team,
+        away_team,
+        home_moneyline: signed(line["homeTeamOdds"]["moneyLine"].as_f64()),
+        away_moneyline: signed(line["awayTeamOdds"]["moneyLine"].as_f64()),
+        // always one decimal on the page, e.g. "223.0"
+        over_under: line["overUnder"].as_f64().map(|total| format!("{:.1}", total)).unwrap_or_default(),
+        numeric: None,
+        consensus: odds::consensus_line(&providers),
+        providers,
+        matchup_predictor: matchup_predictor(&data["predictor"])
+    };
+    game_odds.numeric = Some(odds::numeric_odds(&game_odds));
+    return Some(game_odds);
+}
+
+#[test]
+fn teams_from_espnfitt_test() {
+    let contents = std::fs::read_to_string("./test-data/teams-page.html").unwrap();
+    let teams = parse_with(ParserBackend::Json, PageType::Teams, &contents, |content| teams(content, League::Nba)).unwrap();
+    let mut html_teams = crate::get_teams(contents.to_string(), League::Nba);
+    // the markup lays divisions out in columns, so only the membership has to match
+    let mut json_teams = teams;
+    json_teams.sort_by(|a, b| a.id.cmp(&b.id));
+    html_teams.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(serde_json::to_value(&json_teams).unwrap(), serde_json::to_value(&html_teams).unwrap());
+}
+
+#[test]
+fn completed_games_from_espnfitt_test() {
+    let contents = std::fs::read_to_string("./test-data/brooklyn-home-page-for-previous-games.html").unwrap();
+    let games = parse_with(ParserBackend::Json, PageType::TeamPage, &contents, completed_games).unwrap();
+    assert_eq!(games[0].game_id, "401360612");
+    assert_eq!(games[0].opponent, "Jazz");
+    assert_eq!(games[0].score, "125-102");
+    assert_eq!(games[0].result, "L");
+    assert_eq!(games[0].box_score_link, "http://www.espn.com/nba/boxscore/_/gameId/401360612");
+    let html_games = crate::get_completed_games_from_team_page_html(&contents);
+    assert_eq!(serde_json::to_value(&games).unwrap(), serde_json::to_value(&html_games).unwrap());
+}
+
+#[test]
+fn schedule_from_espnfitt_test() {
+    let team_page = |file: &str| std::fs::read_to_string(format!("./test-data/{}", file)).unwrap();
+    let game_over = team_page("team-page-game-over.html");
+    assert_eq!(parse_with(ParserBackend::Json, PageType::TeamPage, &game_over, latest_game_id), Some(crate::get_latest_game_id(game_over.to_string())));
+    let live = team_page("team-page-live-game.html");
+    assert_eq!(parse_with(ParserBackend::Json, PageType::TeamPage, &live, latest_game_id), Some(crate::get_latest_game_id(live.to_string())));
+    let okc = team_page("okc-home-page-upcoming-game-id.html");
+    assert_eq!(parse_with(ParserBackend::Json, PageType::TeamPage, &okc, upcoming_game_id), crate::find_upcoming_game_id_from_html(&okc));
+    let raptors = team_page("raptors-team-page-upcoming-opponent.html");
+    assert_eq!(parse_with(ParserBackend::Json, PageType::TeamPage, &raptors, upcoming_opponent_name), Some("Hawks".to_string()));
+    assert_eq!(parse_with(ParserBackend::Json, PageType::TeamPage, &raptors, upcoming_game_id), Some("401360609".to_string()));
+}
+
+#[test]
+fn game_odds_from_gamepackage_data_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let odds = parse_with(ParserBackend::Json, PageType::Game, &contents, game_odds).unwrap();
+    assert_eq!(odds.away_team, "phx");
+    assert_eq!(odds.home_team, "tor");
+    assert_eq!(odds.away_spread, "-4.0");
+    assert_eq!(odds.home_spread, "+4");
+    assert_eq!(odds.away_moneyline, "-180");
+    assert_eq!(odds.home_moneyline, "+155");
+    assert_eq!(odds.over_under, "223.0");
+    assert_eq!(odds.numeric.unwrap().favourite, Some("phx".to_string()));
+    assert_eq!(odds.providers.len(), 3);
+    let predictor = odds.matchup_predictor.unwrap();
+    assert_eq!(predictor.home_win_percentage, 50.2);
+    assert_eq!(predictor.away_win_percentage, 49.8);
+
+    let not_found = std::fs::read_to_string("./test-data/game-page-for-odds-not-found.html").unwrap();
+    assert_eq!(parse_with(ParserBackend::Json, PageType::Game, &not_found, game_odds).is_none(), true);
+}
+
+#[test]
+fn game_odds_backends_agree_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let json_odds = parse_with(ParserBackend::Json, PageType::Game, &contents, game_odds).unwrap();
+    let html_odds = crate::get_odds_for_game_html(contents).unwrap();
+    assert_eq!(serde_json::to_value(&json_odds).unwrap(), serde_json::to_value(&html_odds).unwrap());
+}
+
+#[test]
+fn game_odds_consensus_test() {
+    let line = |provider: &str, spread: f64| json!({"provider": {"name": provider}, "spread": spread, "overUnder": 220, "homeTeamOdds": {"moneyLine": 150}, "awayTeamOdds": {"moneyLine": -170}});
+    let mut data = json!({
+        "header": {"competitions": [{"competitors": [
+            {"homeAway": "home", "team": {"abbreviation": "TOR"}},
+            {"homeAway": "away", "team": {"abbreviation": "PHX"}}
+        ]}]},
+        "pickcenter": [line("Caesars Sportsbook", 4.0), line("consensus", 4.5)]
+    });
+    let odds = game_odds(&data).unwrap();
+    // the flat fields stay the first sportsbook's, as the page shows them
+    assert_eq!((odds.home_spread.as_str(), odds.away_spread.as_str()), ("+4", "-4"));
+    assert_eq!(odds.consensus.unwrap().home_spread, Some(4.5));
+    data["pickcenter"] = json!([line("Caesars Sportsbook", 4.0)]);
+    assert_eq!(game_odds(&data).unwrap().consensus, None);
+}
+
+#[test]
+fn team_box_from_gamepackage_data_test() {
+    let data = json!({
+        "header": {"id": "401307733", "competitions": [{"competitors": [
+            {"homeAway": "home", "id": "28", "score": "103", "team": {"abbreviation": "TOR", "name": "Raptors", "logos": [{"href": "https://a.espncdn.com/i/teamlogos/nba/500/tor.png"}]}},
+            {"homeAway": "away", "id": "17", "score": "116", "team": {"abbreviation": "BKN", "name": "Nets", "logos": [{"href": "https://a.espncdn.com/i/teamlogos/nba/500/bkn.png"}]}}
+        ]}]},
+        "boxscore": {"players": [{"team": {"id": "28"}, "statistics": [{
+            "names": ["MIN", "FG", "3PT", "FT", "OREB", "DREB", "REB", "AST", "STL", "BLK", "TO", "PF", "+/-", "PTS"],
+            "athletes": [
+                {"starter": true, "didNotPlay": false, "athlete": {"id": "3134908", "shortName": "P. Siakam", "position": {"abbreviation": "PF"}},
+                    "stats": ["36", "9-19", "1-4", "4-4", "2", "7", "9", "6", "1", "0", "3", "2", "-12", "23"]},
+                {"starter": false, "didNotPlay": true, "reason": "DNP-COACH'S DECISION", "athlete": {"id": "4277843", "shortName": "M. Flynn", "position": {"abbreviation": "G"}}, "stats": []}
+            ]
+        }]}]}
+    });
+    assert_eq!(orientation(&data, "tor").unwrap().to_string(), "home");
+    let team_box = team_box(&data, &HomeOrAway::home, League::Nba).unwrap();
+    assert_eq!(team_box.overview.share_url, "https://www.espn.com/nba/boxscore/_/gameId/401307733");
+    assert_eq!(team_box.overview.event.away_team.id, "bkn");
+    assert_eq!(team_box.overview.score.home.score, "103");
+    assert_eq!(team_box.player_records.len(), 2);
+    let siakam = &team_box.player_records[0].player;
+    assert_eq!(siakam.starter, true);
+    assert_eq!(siakam.field_goals_made, "9");
+    assert_eq!(siakam.field_goals_attempted, "19");
+    assert_eq!(siakam.plus_minus, "-12");
+    assert_eq!(siakam.points, "23");
+    assert_eq!(team_box.player_records[1].player.dnp, "DNP-COACH'S DECISION");
+}
+
+#[test]
+fn falls_back_without_embedded_json_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html").unwrap();
+    assert_eq!(parse_with(ParserBackend::Json, PageType::BoxScore, &contents, |data| team_box(data, &HomeOrAway::home, League::Nba)).is_none(), true);
+    let teams_page = std::fs::read_to_string("./test-data/teams-page.html").unwrap();
+    assert_eq!(parse_with(ParserBackend::Html, PageType::Teams, &teams_page, |content| teams(content, League::Nba)).is_none(), true);
+}
@@ -0,0 +1,288 @@
+use rocket::http::{ContentType, Header, MediaType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::Serialize;
+
+use crate::{GameResult, Player, TeamBox, TeamInjuryReport};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Xlsx
+}
+
+impl ExportFormat {
+    fn from_name(name: &str) -> Option<ExportFormat> {
+        return match name {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            _ => None
+        };
+    }
+}
+
+fn xlsx_content_type() -> ContentType {
+    return ContentType::new("application", "vnd.openxmlformats-officedocument.spreadsheetml.sheet");
+}
+
+// `?format=` wins over the Accept header so a plain link in a browser can download a file
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ExportFormat {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if let Some(Ok(name)) = request.query_value::<&str>("format") {
+            return match ExportFormat::from_name(name) {
+                Some(format) => Outcome::Success(format),
+                None => Outcome::Error((Status::NotAcceptable, format!("unsupported format: {}", name)))
+            };
+        }
+        let preferred = request.accept().map(|accept| accept.preferred().media_type().clone());
+        return match preferred {
+            Some(media_type) if media_type == MediaType::CSV => Outcome::Success(ExportFormat::Csv),
+            Some(media_type) if media_type == *xlsx_content_type().media_type() => Outcome::Success(ExportFormat::Xlsx),
+            _ => Outcome::Success(ExportFormat::Json)
+        };
+    }
+}
+
+pub struct File {
+    body: Vec<u8>,
+    content_type: ContentType,
+    disposition: Header<'static>
+}
+
+// the file is boxed, since a content type and a header dwarf most of the JSON values
+pub enum Exported<T> {
+    Json(Json<T>),
+    File(Box<File>)
+}
+
+// without `?format=` the Accept header picks the body, so shared caches have to key on it
+impl<'r, T: Serialize> Responder<'r, 'static> for Exported<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let response = match self {
+            Exported::Json(json) => json.respond_to(request)?,
+            Exported::File(file) => {
+                let File { body, content_type, disposition } = *file;
+                Response::build_from(body.respond_to(request)?)
+                    .header(content_type)
+                    .header(disposition)
+                    .finalize()
+            }
+        };
+        return Response::build_from(response).raw_header_adjoin("Vary", "Accept").ok();
+    }
+}
+
+// the columns are part of the contract with spreadsheets built on these exports, so only ever append to them
+pub struct Table {
+    // a fixed name, since anything taken from the request could hold characters sheet names can't
+    pub sheet_name: &'static str,
+    pub columns: Vec<&'static str>,
+    // the columns the xlsx export writes as numbers; ids, codes and everything else stay text
+    pub numeric_columns: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>
+}
+
+impl Table {
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record(&self.columns).unwrap();
+        for row in &self.rows {
+            writer.write_record(row).unwrap();
+        }
+        return String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    }
+
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let header = Format::new().set_bold();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(self.sheet_name)?;
+        for (col, column) in self.columns.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, *column, &header)?;
+        }
+        let numeric = self.columns.iter().map(|column| self.numeric_columns.contains(column)).collect::<Vec<bool>>();
+        for (row, values) in self.rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                // stats go in as numbers so editors can sum columns, "-9" plus/minus included. "NaN" and "inf" parse
+                // as floats too, so only finite values count
+                match value.parse::<f64>() {
+                    Ok(number) if numeric[col] && number.is_finite() => worksheet.write_number(row as u32 + 1, col as u16, number)?,
+                    _ => worksheet.write_string(row as u32 + 1, col as u16, value)?
+                };
+            }
+        }
+        return workbook.save_to_buffer();
+    }
+}
+
+// the name comes from the path, so only [a-z0-9-] makes it into the header
+fn attachment(name: &str, extension: &str) -> Header<'static> {
+    let name = name.to_lowercase().chars().filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-').collect::<String>();
+    return Header::new("Content-Disposition", format!("attachment; filename=\"{}.{}\"", name, extension));
+}
+
+pub fn respond<T: Serialize>(format: ExportFormat, name: &str, value: T, table: fn(&T) -> Table) -> Result<Exported<T>, Status> {
+    return match format {
+        ExportFormat::Json => Ok(Exported::Json(Json(value))),
+        ExportFormat::Csv => Ok(Exported::File(Box::new(File { body: table(&value).to_csv().into_bytes(), content_type: ContentType::CSV, disposition: attachment(name, "csv") }))),
+        ExportFormat::Xlsx => match table(&value).to_xlsx() {
+            Ok(body) => Ok(Exported::File(Box::new(File { body, content_type: xlsx_content_type(), disposition: attachment(name, "xlsx") }))),
+            Err(error) => {
+                tracing::error!(name, error = %error, "xlsx_export_failed");
+                Err(Status::InternalServerError)
+            }
+        }
+    };
+}
+
+fn player_row(player: &Player) -> Vec<String> {
+    let box_score = &player.player;
+    return vec![
+        player.id.to_string(),
+        box_score.first_initial_and_last_name.to_string(),
+        player.alignment.to_string(),
+        box_score.starter.to_string(),
+        box_score.position.to_string(),
+        box_score.minutes.to_string(),
+        box_score.field_goals_made.to_string(),
+        box_score.field_goals_attempted.to_string(),
+        box_score.three_point_field_goals_made.to_string(),
+        box_score.three_point_field_goals_attempted.to_string(),
+        box_score.free_throws_made.to_string(),
+        box_score.free_throws_attempted.to_string(),
+        box_score.oreb.to_string(),
+        box_score.dreb.to_string(),
+        box_score.rebounds_total.to_string(),
+        box_score.assists.to_string(),
+        box_score.steals.to_string(),
+        box_score.blocked_shots.to_string(),
+        box_score.turnovers.to_string(),
+        box_score.pf.to_string(),
+        box_score.plus_minus.to_string(),
+        box_score.points.to_string(),
+        box_score.dnp.to_string()
+    ];
+}
+
+const BOX_SCORE_STATS: [&str; 17] = [
+    "minutes", "field_goals_made", "field_goals_attempted", "three_point_field_goals_made", "three_point_field_goals_attempted",
+    "free_throws_made", "free_throws_attempted", "oreb", "dreb", "rebounds_total", "assists", "steals",
+    "blocked_shots", "turnovers", "pf", "plus_minus", "points"
+];
+
+pub fn box_score_table(team_box: &TeamBox) -> Table {
+    let mut columns = vec!["player_id", "name", "alignment", "starter", "position"];
+    columns.extend(BOX_SCORE_STATS);
+    columns.push("dnp");
+    return Table {
+        sheet_name: "Box score",
+        columns,
+        numeric_columns: BOX_SCORE_STATS.to_vec(),
+        rows: team_box.player_records.iter().map(player_row).collect()
+    };
+}
+
+pub fn injuries_table(team_injury_reports: &[TeamInjuryReport]) -> Table {
+    let mut rows = Vec::new();
+    for report in team_injury_reports {
+        for injury in &report.injuries {
+            rows.push(vec![
+                report.team_code.to_string(),
+                report.team_name.to_string(),
+                injury.name.to_string(),
+                injury.position.to_string(),
+                injury.date.to_string(),
+                injury.status.to_string(),
+                injury.description.to_string()
+            ]);
+        }
+    }
+    return Table {
+        sheet_name: "Injuries",
+        columns: vec!["team_code", "team_name", "name", "position", "date", "status", "description"],
+        numeric_columns: vec![],
+        rows
+    };
+}
+
+pub fn previous_results_table(game_results: &[GameResult]) -> Table {
+    return Table {
+        sheet_name: "Previous results",
+        columns: vec!["game_id", "opponent", "at_vs", "result", "score", "box_score_link"],
+        numeric_columns: vec![],
+        rows: game_results.iter().map(|game_result| vec![
+            game_result.game_id.to_string(),
+            game_result.opponent.to_string(),
+            game_result.at_vs.to_string(),
+            game_result.result.to_string(),
+            game_result.score.to_string(),
+            game_result.box_score_link.to_string()
+        ]).collect()
+    };
+}
+
+#[test]
+fn box_score_csv_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html").unwrap();
+    let team_box = crate::get_latest_game_box(&contents, crate::HomeOrAway::home, crate::league::League::Nba);
+    let csv = box_score_table(&team_box).to_csv();
+    let lines = csv.lines().collect::<Vec<&str>>();
+    assert_eq!(lines[0].starts_with("player_id,name,alignment,starter,position,minutes,field_goals_made"), true);
+    assert_eq!(lines.len(), team_box.player_records.len() + 1);
+    assert_eq!(lines[1].split(',').nth(1), Some("P. Siakam"));
+}
+
+#[test]
+fn injuries_csv_quotes_descriptions_test() {
+    let team_injury_reports = crate::injuries(std::fs::read_to_string("./test-data/injuries.html").unwrap());
+    let table = injuries_table(&team_injury_reports);
+    let csv = table.to_csv();
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let records = reader.records().map(|record| record.unwrap()).collect::<Vec<csv::StringRecord>>();
+    assert_eq!(records.len(), table.rows.len());
+    assert_eq!(&records[0][1], "Atlanta Hawks");
+    assert_eq!(records.iter().any(|record| record[6].contains(',')), true);
+}
+
+#[test]
+fn previous_results_xlsx_test() {
+    let game_results = crate::get_completed_games_from_team_page_html(&std::fs::read_to_string("./test-data/brooklyn-home-page-for-previous-games.html").unwrap());
+    let xlsx = previous_results_table(&game_results).to_xlsx().unwrap();
+    use calamine::Reader;
+    let mut workbook = calamine::open_workbook_from_rs::<calamine::Xlsx<_>, _>(std::io::Cursor::new(xlsx)).unwrap();
+    let sheet = workbook.worksheet_range("Previous results").unwrap();
+    assert_eq!(sheet.get((0, 0)), Some(&calamine::Data::String("game_id".to_string())));
+    assert_eq!(sheet.get((0, 5)), Some(&calamine::Data::String("box_score_link".to_string())));
+    // an id that happens to be all digits stays text
+    assert_eq!(sheet.get((1, 0)), Some(&calamine::Data::String("401360612".to_string())));
+    assert_eq!(sheet.get((1, 4)), Some(&calamine::Data::String("125-102".to_string())));
+}
+
+#[test]
+fn box_score_xlsx_numbers_test() {
+    let table = Table {
+        sheet_name: "Box score",
+        columns: vec!["player_id", "minutes", "points"],
+        numeric_columns: vec!["minutes", "points"],
+        rows: vec![vec!["3134908".to_string(), "36".to_string(), "NaN".to_string()]]
+    };
+    use calamine::Reader;
+    let mut workbook = calamine::open_workbook_from_rs::<calamine::Xlsx<_>, _>(std::io::Cursor::new(table.to_xlsx().unwrap())).unwrap();
+    let sheet = workbook.worksheet_range("Box score").unwrap();
+    assert_eq!(sheet.get((1, 0)), Some(&calamine::Data::String("3134908".to_string())));
+    assert_eq!(sheet.get((1, 1)), Some(&calamine::Data::Float(36.0)));
+    assert_eq!(sheet.get((1, 2)), Some(&calamine::Data::String("NaN".to_string())));
+}
+
+#[test]
+fn attachment_filename_test() {
+    assert_eq!(attachment("bkn-previous-results", "xlsx").value(), "attachment; filename=\"bkn-previous-results.xlsx\"");
+    assert_eq!(attachment("A:b\"/..\\x-box-score", "csv").value(), "attachment; filename=\"abx-box-score.csv\"");
+}
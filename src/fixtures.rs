@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Serialize, Deserialize};
+
+use crate::league::League;
+use crate::page_cache;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixtureRecorderConfig {
+    // when set, every page fetched from ESPN is also saved there as a fixture, e.g. "test-data"
+    #[serde(default)]
+    pub fixture_record_path: Option<String>
+}
+
+// which parsers a saved page feeds
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PageKind {
+    BoxScore,
+    TeamPage,
+    GamePage,
+    Schedule,
+    Injuries,
+    Teams,
+    SiteApiTeams,
+    SiteApiInjuries,
+    SiteApiSchedule,
+    SiteApiSummary
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Fixture {
+    pub kind: PageKind,
+    pub league: League,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<DateTime<Utc>>
+}
+
+// maps each fixture's file name to what it is, so the golden tests know which parsers to run over it
+pub const MANIFEST: &str = "fixtures.json";
+
+// a missing manifest is an empty one; one that doesn't parse is an error rather than something to write over
+pub fn read_manifest(dir: &Path) -> Result<BTreeMap<String, Fixture>, String> {
+    let path = dir.join(MANIFEST);
+    return match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(|error| format!("could not parse {}: {}", path.display(), error)),
+        Err(_) => Ok(BTreeMap::new())
+    };
+}
+
+fn fixture(kind: PageKind, league: League, team_code: Option<&str>, game_id: Option<&str>) -> Option<Fixture> {
+    return Some(Fixture {
+        kind,
+        league,
+        team_code: team_code.map(|team_code| team_code.to_string()),
+        game_id: game_id.map(|game_id| game_id.to_string()),
+        url: None,
+        recorded_at: None
+    });
+}
+
+// works out what a page is from the URL it was fetched from; None for pages no parser reads
+pub fn describe(url: &str) -> Option<Fixture> {
+    let url = Url::parse(url).ok()?;
+    let segments = url.path_segments()?.filter(|segment| !segment.is_empty()).collect::<Vec<&str>>();
+    // matched on the path rather than the host, so pages from a configured stand-in for ESPN are described too
+    if segments.first() == Some(&"apis") {
+        let (slug, rest) = match segments.as_slice() {
+            ["apis", "site", "v2", "sports", "basketball", slug, rest @ ..] => (*slug, rest),
+            _ => return None
+        };
+        let league = League::ALL.iter().copied().find(|league| league.site_api_slug() == slug)?;
+        let event = url.query_pairs().find(|(key, _)| key == "event").map(|(_, value)| value.to_string());
+        return match rest {
+            ["teams"] => fixture(PageKind::SiteApiTeams, league, None, None),
+            ["injuries"] => fixture(PageKind::SiteApiInjuries, league, None, None),
+            ["teams", team_code, "schedule"] => fixture(PageKind::SiteApiSchedule, league, Some(team_code), None),
+            ["summary"] => fixture(PageKind::SiteApiSummary, league, None, Some(event.as_deref()?)),
+            _ => None
+        };
+    }
+    let (league, rest) = match segments.split_first() {
+        Some((league, rest)) => (League::from_path(league)?, rest),
+        None => return None
+    };
+    return match rest {
+        ["boxscore", "_", "gameId", game_id, ..] => fixture(PageKind::BoxScore, league, None, Some(game_id)),
+        ["game", "_", "gameId", game_id, ..] => fixture(PageKind::GamePage, league, None, Some(game_id)),
+        ["team", "schedule", "_", "name", team_code, ..] => fixture(PageKind::Schedule, league, Some(team_code), None),
+        ["team", "_", "name", team_code, ..] => fixture(PageKind::TeamPage, league, Some(team_code), None),
+        ["injuries"] => fixture(PageKind::Injuries, league, None, None),
+        ["teams"] => fixture(PageKind::Teams, league, None, None),
+        _ => None
+    };
+}
+
+pub struct FixtureRecorder {
+    dir: PathBuf,
+    // the manifest is read, updated and rewritten per page, so concurrent fetches take turns
+    manifest: Mutex<()>
+}
+
+impl FixtureRecorder {
+    pub fn open(dir: PathBuf) -> Result<FixtureRecorder, String> {
+        fs::create_dir_all(&dir).map_err(|error| format!("could not create {}: {}", dir.display(), error))?;
+        read_manifest(&dir)?;
+        return Ok(FixtureRecorder { dir, manifest: Mutex::new(()) });
+    }
+
+    pub fn record(&self, url: &str, body: &[u8]) {
+        let fixture = match describe(url) {
+            Some(fixture) => Fixture { url: Some(url.to_string()), recorded_at: Some(Utc::now()), ..fixture },
+            None => return
+        };
+        let extension = match fixture.kind {
+            PageKind::SiteApiTeams | PageKind::SiteApiInjuries | PageKind::SiteApiSchedule | PageKind::SiteApiSummary => "json",
+            _ => "html"
+        };
+        let file = format!("{}.{}", page_cache::slug(url), extension);
+        let _lock = self.manifest.lock().unwrap();
+        let _ = fs::write(self.dir.join(&file), body);
+        let mut manifest = match read_manifest(&self.dir) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                tracing::warn!(error = error.as_str(), url, "fixture_manifest_unreadable");
+                return;
+            }
+        };
+        manifest.insert(file, fixture);
+        let _ = fs::write(self.dir.join(MANIFEST), serde_json::to_string_pretty(&manifest).unwrap() + "\n");
+    }
+}
+
+fn configured() -> &'static OnceLock<FixtureRecorder> {
+    static RECORDER: OnceLock<FixtureRecorder> = OnceLock::new();
+    return &RECORDER;
+}
+
+pub fn configure(recorder: FixtureRecorder) {
+    let _ = configured().set(recorder);
+}
+
+pub fn recorder() -> Option<&'static FixtureRecorder> {
+    return configured().get();
+}
+
+#[test]
+fn describe_test() {
+    let box_score = describe("https://www.espn.com/nba/boxscore/_/gameId/401307733").unwrap();
+    assert_eq!((box_score.kind, box_score.league, box_score.game_id.as_deref()), (PageKind::BoxScore, League::Nba, Some("401307733")));
+    let team_page = describe("https://www.espn.com/nba/team/_/name/tor").unwrap();
+    assert_eq!((team_page.kind, team_page.league, team_page.team_code.as_deref()), (PageKind::TeamPage, League::Nba, Some("tor")));
+    assert_eq!(describe("https://www.espn.com/nba/team/schedule/_/name/tor/season/2022").unwrap().kind, PageKind::Schedule);
+    let summary = describe("https://site.api.espn.com/apis/site/v2/sports/basketball/nba/summary?event=401360432").unwrap();
+    assert_eq!((summary.kind, summary.league, summary.game_id.as_deref()), (PageKind::SiteApiSummary, League::Nba, Some("401360432")));
+    assert_eq!(describe("https://www.espn.com/wnba/team/_/name/lv").unwrap().league, League::Wnba);
+    assert_eq!(describe("https://www.espn.com/mens-college-basketball/team/_/name/150").unwrap().league, League::MensCollegeBasketball);
+    let g_league = describe("https://site.api.espn.com/apis/site/v2/sports/basketball/nba-development/teams").unwrap();
+    assert_eq!((g_league.kind, g_league.league), (PageKind::SiteApiTeams, League::NbaGLeague));
+    assert_eq!(describe("https://www.espn.com/nba/scoreboard"), None);
+}
+
+#[test]
+fn open_rejects_unreadable_manifest_test() {
+    let dir = std::env::temp_dir().join(format!("qr-fixtures-unreadable-manifest-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(MANIFEST), "{not json").unwrap();
+    assert_eq!(FixtureRecorder::open(dir.clone()).is_err(), true);
+    fs::write(dir.join(MANIFEST), "{}").unwrap();
+    assert_eq!(FixtureRecorder::open(dir.clone()).is_ok(), true);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::fixtures::{self, Fixture, PageKind};
+use crate::{matchups, odds, preview, site_api, HomeOrAway, Team};
+
+const FIXTURES_DIR: &str = "./test-data";
+const GOLDEN_DIR: &str = "./test-data/golden";
+const TEAMS_FIXTURE: &str = "teams-page.html";
+
+// a parser that panics on a fixture records the panic, so fixing it shows up as a diff like any other change
+fn run<T: Serialize>(outputs: &mut BTreeMap<String, Value>, parser: &str, parse: impl FnOnce() -> T) {
+    let output = match panic::catch_unwind(AssertUnwindSafe(parse)) {
+        Ok(output) => serde_json::to_value(output).unwrap(),
+        Err(error) => json!({"panicked": crate::scrape_health::panic_message(error)})
+    };
+    outputs.insert(parser.to_string(), output);
+}
+
+// every parser that can read this kind of page, keyed by parser name
+fn parse_fixture(
+    fixture: &Fixture,
+    contents: &str,
+    // the team registry the server would have loaded, for opponents outside the hardcoded table
+    teams: &[Team]
+) -> Value {
+    let mut outputs = BTreeMap::new();
+    let league = fixture.league;
+    let html = contents.to_string();
+    let team_code = fixture.team_code.clone().unwrap_or_default();
+    let game_id = fixture.game_id.clone().unwrap_or_default();
+    match fixture.kind {
+        PageKind::BoxScore => {
+            run(&mut outputs, "get_game_header", || crate::get_game_header(&html));
+            run(&mut outputs, "get_latest_game_box.away", || crate::get_latest_game_box(&html, HomeOrAway::away, league));
+            run(&mut outputs, "get_latest_game_box.home", || crate::get_latest_game_box(&html, HomeOrAway::home, league));
+            run(&mut outputs, "get_meeting_from_box_score_html", || matchups::get_meeting_from_box_score_html(league, &game_id, &html, None));
+            if fixture.team_code.is_some() {
+                run(&mut outputs, "get_orientation", || crate::get_orientation(&html, &team_code).map(|home_or_away| home_or_away.to_string()));
+                run(&mut outputs, "get_probable_lineup_from_box_score_html", || crate::get_probable_lineup_from_box_score_html(&html, &team_code, league));
+            }
+        }
+        PageKind::TeamPage => {
+            run(&mut outputs, "get_completed_games_from_team_page_html", || crate::get_completed_games_from_team_page_html(&html));
+            run(&mut outputs, "get_previous_results_from_team_page_html", || crate::get_previous_results_from_team_page_html(html.to_string()));
+            run(&mut outputs, "find_upcoming_game_id_from_html", || crate::find_upcoming_game_id_from_html(&html));
+            run(&mut outputs, "find_latest_game_id", || crate::find_latest_game_id(&html));
+            run(&mut outputs, "find_upcoming_opponent_team_code", || crate::find_upcoming_opponent_team_code(&html, league, teams));
+        }
+        PageKind::GamePage => {
+            run(&mut outputs, "get_odds_for_game_html", || crate::get_odds_for_game_html(html.to_string()));
+            run(&mut outputs, "get_game_info_from_html", || preview::get_game_info_from_html(&game_id, &html));
+            run(&mut outputs, "get_season_series_from_html", || preview::get_season_series_from_html(&html));
+            run(&mut outputs, "get_venue_from_html", || preview::get_venue_from_html(&html));
+            run(&mut outputs, "get_provider_lines_from_html", || odds::get_provider_lines_from_html(&html));
+        }
+        PageKind::Schedule => run(&mut outputs, "get_games_from_schedule_html", || matchups::get_games_from_schedule_html(&html)),
+        PageKind::Injuries => run(&mut outputs, "injuries", || crate::injuries(html.to_string())),
+        PageKind::Teams => run(&mut outputs, "get_teams", || crate::get_teams(html.to_string(), league)),
+        PageKind::SiteApiTeams => run(&mut outputs, "teams_from_json", || site_api::teams_from_json(&serde_json::from_str(contents).unwrap())),
+        PageKind::SiteApiInjuries => run(&mut outputs, "injuries_from_json", || site_api::injuries_from_json(&serde_json::from_str(contents).unwrap())),
+        PageKind::SiteApiSchedule => run(&mut outputs, "latest_game_id_from_schedule", || site_api::latest_game_id_from_schedule(&serde_json::from_str(contents).unwrap())),
+        PageKind::SiteApiSummary => {
+            let summary = serde_json::from_str::<Value>(contents).unwrap();
+            run(&mut outputs, "game_odds", || crate::embedded_json::game_odds(&summary));
+            if fixture.team_code.is_some() {
+                run(&mut outputs, "team_box_from_summary", || site_api::team_box_from_summary(&summary, &team_code, league));
+            }
+        }
+    }
+    return Value::Object(outputs.into_iter().collect());
+}
+
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    return match text.chars().count() > 80 {
+        true => format!("{}...", text.chars().take(80).collect::<String>()),
+        false => text
+    };
+}
+
+// JSON pointers to everything that differs, e.g. `/get_latest_game_box.home/player_records/0/player/points: "21" -> "23"`
+fn diff(pointer: &str, expected: &Value, actual: &Value, diffs: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for key in expected.keys().chain(actual.keys().filter(|key| !expected.contains_key(*key))) {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                diff(&child, expected.get(key).unwrap_or(&Value::Null), actual.get(key).unwrap_or(&Value::Null), diffs);
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(&format!("{}/{}", pointer, i), expected, actual, diffs);
+            }
+        }
+        _ if expected != actual => diffs.push(format!("{}: {} -> {}", pointer, short(expected), short(actual))),
+        _ => ()
+    }
+}
+
+// runs every parser over every fixture in test-data/fixtures.json and compares the full output with
+// test-data/golden/<fixture>.json. `UPDATE_GOLDEN=1 cargo test golden` rewrites the snapshots after a deliberate change
+#[test]
+fn golden_files_test() {
+    let update = std::env::var("UPDATE_GOLDEN").map(|value| value == "1").unwrap_or(false);
+    let manifest = fixtures::read_manifest(Path::new(FIXTURES_DIR)).unwrap();
+    assert_eq!(manifest.is_empty(), false);
+    let teams_page = fs::read_to_string(Path::new(FIXTURES_DIR).join(TEAMS_FIXTURE)).unwrap();
+    let teams = crate::get_teams(teams_page, manifest[TEAMS_FIXTURE].league);
+    let mut failures = Vec::new();
+    for (file, fixture) in &manifest {
+        let contents = fs::read_to_string(Path::new(FIXTURES_DIR).join(file)).unwrap();
+        let output = parse_fixture(fixture, &contents, &teams);
+        let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+        let golden_path = Path::new(GOLDEN_DIR).join(format!("{}.json", stem));
+        if update {
+            fs::create_dir_all(GOLDEN_DIR).unwrap();
+            fs::write(&golden_path, serde_json::to_string_pretty(&output).unwrap() + "\n").unwrap();
+            continue;
+        }
+        let expected = match fs::read_to_string(&golden_path) {
+            Ok(expected) => serde_json::from_str::<Value>(&expected).unwrap(),
+            Err(_) => {
+                failures.push(format!("{}: no snapshot at {}", file, golden_path.display()));
+                continue;
+            }
+        };
+        let mut diffs = Vec::new();
+        diff("", &expected, &output, &mut diffs);
+        let total = diffs.len();
+        failures.extend(diffs.into_iter().take(20).map(|diff| format!("{}: {}", file, diff)));
+        if total > 20 {
+            failures.push(format!("{}: ...and {} more", file, total - 20));
+        }
+    }
+    assert!(failures.is_empty(), "parser output changed; if that's intended, rerun with UPDATE_GOLDEN=1 and commit the snapshots\n{}", failures.join("\n"));
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, routes, Route, State};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::api_keys::ApiKeys;
+use crate::league::{League, LeagueConfig};
+use crate::site_api::{self, DataSource, Resource};
+use crate::{fixtures, page_cache, upstream, Team};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadinessConfig {
+    // seconds between attempts at the startup team registry load while ESPN is unreachable
+    #[serde(default = "default_team_registry_retry_interval")]
+    pub team_registry_retry_interval: u64
+}
+
+fn default_team_registry_retry_interval() -> u64 {
+    return 10;
+}
+
+// filled in as each league's startup team registry load succeeds; the instance stays unready until every league has
+#[derive(Clone, Default)]
+pub struct Readiness {
+    teams: Arc<Mutex<HashMap<League, Vec<Team>>>>
+}
+
+async fn load_team_registry(league: League) -> Result<Vec<Team>, String> {
+    if site_api::source(Resource::Teams) == DataSource::SiteApi {
+        if let Some(teams) = site_api::get_teams(league).await {
+            return Ok(teams);
+        }
+    }
+    let response = upstream::get(league.url("teams")).await.map_err(|error| error.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("ESPN answered {}", response.status()));
+    }
+    let html = response.text().await.map_err(|error| error.to_string())?;
+    let teams = panic::catch_unwind(AssertUnwindSafe(|| crate::get_teams(html, league))).map_err(crate::scrape_health::panic_message)?;
+    if teams.is_empty() {
+        return Err("the teams page listed no teams".to_string());
+    }
+    return Ok(teams);
+}
+
+// retries until ESPN answers, so an instance started during an outage becomes ready on its own
+pub fn spawn_team_registry_load(readiness: Readiness, league: League, retry_interval: u64) {
+    tokio::spawn(async move {
+        loop {
+            match load_team_registry(league).await {
+                Ok(teams) => {
+                    tracing::info!(league = league.path(), teams = teams.len(), "team_registry_loaded");
+                    readiness.teams.lock().unwrap().insert(league, teams);
+                    return;
+                }
+                Err(error) => {
+                    tracing::warn!(league = league.path(), error = error.as_str(), retry_in = retry_interval, "team_registry_load_failed");
+                    tokio::time::sleep(Duration::from_secs(retry_interval.max(1))).await;
+                }
+            }
+        }
+    });
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Check {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    // always ok once serving: a config that fails validation stops launch
+    pub config: Check,
+    pub team_registry: Check,
+    // ESPN answered the last fetch, or the page cache has something to serve if it doesn't
+    pub upstream: Check
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Version {
+    pub version: String,
+    pub git_commit: String,
+    pub build_time: Option<DateTime<Utc>>,
+    // optional behaviour switched on in this instance's config, e.g. "page_cache" or "site_api.odds"
+    pub features: Vec<String>
+}
+
+pub fn routes() -> Vec<Route> {
+    return routes![live, ready, version];
+}
+
+#[utoipa::path(
+    get, path = "/health/live", tag = "health",
+    responses((status = 200, description = "The process is up"))
+)]
+#[get("/health/live")]
+pub fn live() -> Json<serde_json::Value> {
+    return Json(json!({"status": "live"}));
+}
+
+fn upstream_check() -> Check {
+    let warm = page_cache::cache().map(|cache| cache.is_warm()).unwrap_or(false);
+    let reachability = upstream::reachability();
+    // e.g. "ESPN failed 3 of the last 20 fetches"
+    let failed = format!("ESPN failed {} of the last {} fetches", reachability.failures(), reachability.fetches());
+    return match (reachability.fetches(), reachability.reachable(), warm) {
+        (_, true, _) => Check { ok: true, detail: Some(format!("{}, last answered at {}", failed, reachability.last_answered_at.unwrap().to_rfc3339())) },
+        (0, _, warm) => Check { ok: warm, detail: Some("nothing fetched yet".to_string()) },
+        (_, false, true) => Check { ok: true, detail: Some(format!("{}, serving from the page cache", failed)) },
+        (_, false, false) => Check { ok: false, detail: Some(format!("{} and the page cache is empty", failed)) }
+    };
+}
+
+#[utoipa::path(
+    get, path = "/health/ready", tag = "health",
+    responses(
+        (status = 200, description = "Ready for traffic", body = ReadinessReport),
+        (status = 503, description = "Still loading the team registry, or most recent ESPN fetches failed with nothing cached", body = ReadinessReport)
+    )
+)]
+#[get("/health/ready")]
+pub fn ready(readiness: &State<Readiness>, league_config: &State<LeagueConfig>) -> (Status, Json<ReadinessReport>) {
+    let config = Check { ok: true, detail: None };
+    let teams = readiness.teams.lock().unwrap();
+    // e.g. "nba: 30 teams"
+    let details = league_config.leagues.iter()
+        .map(|league| match teams.get(league) {
+            Some(teams) => format!("{}: {} teams", league.path(), teams.len()),
+            None => format!("{}: loading", league.path())
+        })
+        .collect::<Vec<String>>();
+    let team_registry = Check { ok: league_config.leagues.iter().all(|league| teams.contains_key(league)), detail: Some(details.join(", ")) };
+    let upstream = upstream_check();
+    let ready = config.ok && team_registry.ok && upstream.ok;
+    let status = match ready {
+        true => Status::Ok,
+        false => Status::ServiceUnavailable
+    };
+    return (status, Json(ReadinessReport { ready, config, team_registry, upstream }));
+}
+
+fn features(api_keys: &ApiKeys) -> Vec<String> {
+    let mut features = Vec::new();
+    if api_keys.required() {
+        features.push("api_keys".to_string());
+    }
+    if page_cache::cache().is_some() {
+        features.push("page_cache".to_string());
+    }
+    if fixtures::recorder().is_some() {
+        features.push("fixture_recorder".to_string());
+    }
+    for (name, resource) in [("teams", Resource::Teams), ("injuries", Resource::Injuries), ("box_score", Resource::BoxScore), ("odds", Resource::Odds)] {
+        if site_api::source(resource) == DataSource::SiteApi {
+            features.push(format!("site_api.{}", name));
+        }
+    }
+    return features;
+}
+
+#[utoipa::path(
+    get, path = "/version", tag = "health",
+    responses((status = 200, description = "What's running", body = Version))
+)]
+#[get("/version")]
+pub fn version(api_keys: &State<ApiKeys>) -> Json<Version> {
+    // both set by build.rs
+    let build_time = env!("BUILD_TIMESTAMP").parse::<i64>().ok().and_then(|seconds| Utc.timestamp_opt(seconds, 0).single());
+    return Json(Version {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("GIT_COMMIT").to_string(),
+        build_time,
+        features: features(api_keys)
+    });
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use rocket::request::FromParam;
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::Team;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+// every league shares the NBA's page layouts, but only NBA pages have been captured into test-data so far, so the
+// other leagues' parsing is unchecked until their pages are recorded with `fixture_record_path`
+pub enum League {
+    Nba,
+    Wnba,
+    NbaGLeague,
+    MensCollegeBasketball,
+    WomensCollegeBasketball
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeagueConfig {
+    // leagues the background jobs cover: odds snapshots and the team registries /health/ready waits on. every league's
+    // routes are served either way, e.g. `leagues = ["nba", "wnba"]` for the WNBA site
+    #[serde(default = "default_leagues")]
+    pub leagues: Vec<League>
+}
+
+fn default_leagues() -> Vec<League> {
+    return vec![League::Nba];
+}
+
+impl League {
+    pub const ALL: [League; 5] = [League::Nba, League::Wnba, League::NbaGLeague, League::MensCollegeBasketball, League::WomensCollegeBasketball];
+
+    // the segment ESPN uses in www.espn.com URLs, which is also what our routes accept
+    pub fn path(&self) -> &'static str {
+        return match self {
+            League::Nba => "nba",
+            League::Wnba => "wnba",
+            League::NbaGLeague => "nba-g-league",
+            League::MensCollegeBasketball => "mens-college-basketball",
+            League::WomensCollegeBasketball => "womens-college-basketball"
+        };
+    }
+
+    // the site API names the G League after its old name, the NBA Development League
+    pub fn site_api_slug(&self) -> &'static str {
+        return match self {
+            League::NbaGLeague => "nba-development",
+            _ => self.path()
+        };
+    }
+
+    pub fn from_path(path: &str) -> Option<League> {
+        return match path {
+            "nba" => Some(League::Nba),
+            "wnba" => Some(League::Wnba),
+            "nba-g-league" | "g-league" => Some(League::NbaGLeague),
+            "mens-college-basketball" | "ncaam" => Some(League::MensCollegeBasketball),
+            "womens-college-basketball" | "ncaaw" => Some(League::WomensCollegeBasketball),
+            _ => None
+        };
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        return format!("{}/{}/{}", crate::upstream::base_url(), self.path(), path);
+    }
+
+    pub fn headshot_url(&self, player_id: &str) -> String {
+        return format!("https://a.espncdn.com/combiner/i?img=/i/headshots/{}/players/full/{}.png&w=350&h=254", self.path(), player_id);
+    }
+
+    pub fn logo_url(&self, team_code: &str) -> String {
+        return format!("https://a.espncdn.com/combiner/i?img=/i/teamlogos/{}/500/{}.png&h=100&w=100", self.path(), team_code);
+    }
+}
+
+impl<'a> FromParam<'a> for League {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        return League::from_path(param).ok_or(param);
+    }
+}
+
+// schedule strips only show a team's short name, e.g. "Raptors"
+pub fn team_registry(league: League) -> HashMap<&'static str, &'static str> {
+    let mut map = HashMap::new();
+    match league {
+        League::Nba => {
+            map.insert("Celtics", "bos");
+            map.insert("Nets", "bkn");
+            map.insert("Knicks", "ny");
+            map.insert("76ers", "phi");
+            map.insert("Raptors", "tor");
+            map.insert("Bulls", "chi");
+            map.insert("Cavaliers", "cle");
+            map.insert("Pistons", "det");
+            map.insert("Pacers", "ind");
+            map.insert("Bucks", "mil");
+            map.insert("Hawks", "atl");
+            map.insert("Hornets", "cha");
+            map.insert("Heat", "mia");
+            map.insert("Magic", "orl");
+            map.insert("Wizards", "was");
+            map.insert("Warriors", "gs");
+            map.insert("Clippers", "lac");
+            map.insert("Lakers", "lal");
+            map.insert("Suns", "phx");
+            map.insert("Kings", "sac");
+            map.insert("Nuggets", "den");
+            map.insert("Timberwolves", "min");
+            map.insert("Thunder", "okc");
+            map.insert("Trail Blazers", "por");
+            map.insert("Jazz", "uta");
+            map.insert("Mavericks", "dal");
+            map.insert("Rockets", "hou");
+            map.insert("Grizzlies", "mem");
+            map.insert("Pelicans", "no");
+            map.insert("Spurs", "sa");
+        }
+        League::Wnba => {
+            map.insert("Dream", "atl");
+            map.insert("Sky", "chi");
+            map.insert("Sun", "conn");
+            map.insert("Fever", "ind");
+            map.insert("Liberty", "ny");
+            map.insert("Mystics", "wsh");
+            map.insert("Wings", "dal");
+            map.insert("Aces", "lv");
+            map.insert("Sparks", "la");
+            map.insert("Lynx", "min");
+            map.insert("Mercury", "phx");
+            map.insert("Storm", "sea");
+            map.insert("Valkyries", "gs");
+        }
+        // G League and college rosters are too large and change too often to keep here
+        _ => ()
+    }
+    return map;
+}
+
+// falls back to the league's teams page when the short name isn't registered
+pub fn team_code_from_short_name(league: League, short_name: &str, teams: &[Team]) -> Option<String> {
+    if let Some(team_code) = team_registry(league).get(short_name) {
+        return Some(team_code.to_string());
+    }
+    return teams.iter()
+        .find(|team| team.full_name.ends_with(short_name))
+        .map(|team| team.id.to_string());
+}
+
+#[test]
+fn league_paths_test() {
+    for league in League::ALL {
+        assert_eq!(League::from_path(league.path()), Some(league));
+    }
+    assert_eq!(League::from_path("nfl"), None);
+    assert_eq!(League::Wnba.url("team/_/name/lv"), "https://www.espn.com/wnba/team/_/name/lv");
+    assert_eq!(League::from_path("ncaaw"), Some(League::WomensCollegeBasketball));
+    assert_eq!(League::NbaGLeague.site_api_slug(), "nba-development");
+    assert_eq!(League::MensCollegeBasketball.site_api_slug(), "mens-college-basketball");
+}
+
+#[test]
+fn team_code_from_short_name_test() {
+    assert_eq!(team_code_from_short_name(League::Nba, "Hawks", &[]), Some("atl".to_string()));
+    assert_eq!(team_code_from_short_name(League::Nba, "Bulls", &[]), Some("chi".to_string()));
+    assert_eq!(team_code_from_short_name(League::Wnba, "Aces", &[]), Some("lv".to_string()));
+    // the registries are separate, e.g. the Sun only play in the WNBA
+    assert_eq!(team_code_from_short_name(League::Nba, "Sun", &[]), None);
+    let teams = [Team { id: "sea".to_string(), full_name: "Seattle SuperSonics".to_string() }];
+    assert_eq!(team_code_from_short_name(League::Nba, "SuperSonics", &teams), Some("sea".to_string()));
+    assert_eq!(team_code_from_short_name(League::Nba, "Huskies", &teams), None);
+    // college rosters only come from the teams page
+    let teams = [Team { id: "gonz".to_string(), full_name: "Gonzaga Bulldogs".to_string() }];
+    assert_eq!(team_code_from_short_name(League::MensCollegeBasketball, "Bulldogs", &teams), Some("gonz".to_string()));
+    assert_eq!(team_code_from_short_name(League::MensCollegeBasketball, "Huskies", &teams), None);
+}
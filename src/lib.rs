@@ -0,0 +1,1437 @@
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+use scraper::{Html, ElementRef};
+use std::string::ToString;
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+use rocket::serde::json::{Json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use rocket::{catchers, delete, get, post, routes};
+use rocket::http::{ContentType, Status};
+
+#[macro_use]
+extern crate serde_json;
+
+mod api_keys;
+mod ats;
+mod cors;
+mod embedded_json;
+mod export;
+pub mod fixtures;
+#[cfg(test)]
+mod golden;
+mod health;
+pub mod league;
+pub mod logging;
+mod matchups;
+mod metrics;
+mod odds;
+mod odds_history;
+mod openapi;
+pub mod page_cache;
+pub mod preview;
+mod scrape_health;
+mod selectors;
+mod site_api;
+pub mod upstream;
+pub mod v1;
+pub mod v2;
+mod widgets;
+use scrape_health::{tracked_select, tracked_select_optional};
+use odds::{MatchupPredictor, NumericOdds, ProviderLine};
+use odds_history::{OddsHistory, OddsHistoryConfig, OddsHistoryStore};
+use rocket::State;
+use rocket::fairing::AdHoc;
+use std::path::PathBuf;
+use embedded_json::PageType;
+use site_api::{DataSource, Resource};
+use league::League;
+use export::{ExportFormat, Exported};
+use rocket::response::content;
+use widgets::Theme;
+use api_keys::{ApiClient, ApiKeys};
+
+// the original unversioned paths, kept as aliases of their /v1 equivalents for existing integrations.
+// ranked below everything else since `/<league>/...` also matches `/v1/...` and `/widgets/...`
+#[get("/<league>/box/<team_code>", rank = 2)]
+async fn box_score(league: League, team_code: &str, format: ExportFormat, client: ApiClient) -> Result<Exported<TeamBox>, Status> {
+    return v1::box_score(league, team_code, format, client).await;
+}
+
+#[get("/<league>/previous-results/<team_code>", rank = 2)]
+async fn previous_results(league: League, team_code: String, format: ExportFormat, client: ApiClient) -> Result<Exported<Vec<GameResult>>, Status> {
+    return v1::previous_results(league, team_code, format, client).await;
+}
+
+#[get("/<league>/upcoming-probable-lineup/<team_code>", rank = 2)]
+async fn get_probable_lineups(league: League, team_code: String, client: ApiClient) -> Result<Json<UpcomingLineups>, Status> {
+    return v1::probable_lineup(league, team_code, client).await;
+}
+
+// Err(NotFound) for an unknown team, or one without an upcoming game
+pub async fn get_upcoming_lineups(league: League, team_code: &str) -> Result<UpcomingLineups, Status> {
+    let team_page_html = get_page(league.url(&format!("team/_/name/{}", team_code))).await?;
+    // the teams page is only fetched for an opponent missing from the league's registry, e.g. an expansion team
+    let opponent_team_code = match find_upcoming_opponent_team_code(&team_page_html, league, &[]) {
+        Some(team_code) => team_code,
+        None => find_upcoming_opponent_team_code(&team_page_html, league, &fetch_teams(league).await?).ok_or(Status::NotFound)?
+    };
+    let upcoming_game_id = find_upcoming_game_id_from_html(&team_page_html).ok_or(Status::NotFound)?;
+
+    let injuries = get_injuries_with_team_code(league).await?;
+
+    let game_odds = get_odds_for_game(league, upcoming_game_id).await;
+
+    let team_probable_lineup = get_probable_lineup(league, team_code, &injuries).await?;
+    let opponent_team_probable_lineup = get_probable_lineup(league, &opponent_team_code, &injuries).await?;
+
+    return Ok(UpcomingLineups {
+        team: team_probable_lineup,
+        opponent: opponent_team_probable_lineup,
+        odds: game_odds
+    });
+}
+
+pub async fn get_probable_lineup(league: League, team_code: &str, injuries: &[TeamInjuryReport]) -> Result<ProbableLineup, Status> {
+    // a team without a completed game to go on gets an empty lineup, but ESPN failing fails the request
+    let team_box_score = match get_team_box_score(league, team_code).await {
+        Ok(team_box_score) => Some(team_box_score),
+        Err(status) if status == Status::NotFound => None,
+        Err(status) => return Err(status)
+    };
+    let option = injuries.iter().find(|tij| tij.team_code == team_code);
+    return Ok(ProbableLineup {
+        team_code: team_code.to_owned(),
+        lineup_by_position: team_box_score.map(|team_box_score| probable_lineups(&team_box_score.player_records)).unwrap_or_default(),
+        injury_report: match option {
+            None => TeamInjuryReport {
+                team_code: team_code.to_string(),
+                team_name: team_code.to_string(),
+                injuries: vec![]
+            },
+            _ => option.unwrap().clone()
+        },
+        previous_results: get_previous_results(league, team_code.to_string()).await?
+    });
+}
+
+#[get("/<league>/games/<game_id>/preview", rank = 2)]
+async fn game_preview(league: League, game_id: &str, client: ApiClient) -> Result<Json<preview::GamePreview>, Status> {
+    return v1::game_preview(league, game_id, client).await;
+}
+
+#[get("/<league>/games/<game_id>/odds", rank = 2)]
+async fn game_odds(league: League, game_id: String, client: ApiClient) -> Result<Json<GameOdds>, Status> {
+    return v1::game_odds(league, game_id, client).await;
+}
+
+#[get("/<league>/games/<game_id>/odds/history", rank = 2)]
+fn game_odds_history(league: League, game_id: &str, store: &State<OddsHistoryStore>, client: ApiClient) -> Option<Json<OddsHistory>> {
+    return v1::game_odds_history(league, game_id, store, client);
+}
+
+#[get("/<league>/teams/<team_code>/ats?<last>", rank = 2)]
+async fn team_ats(league: League, team_code: &str, last: Option<usize>, store: &State<OddsHistoryStore>, client: ApiClient) -> Result<Json<ats::AtsReport>, Status> {
+    return v1::ats_report(league, team_code, last, store, client).await;
+}
+
+#[get("/<league>/matchups/<team_a>/<team_b>?<seasons>", rank = 2)]
+async fn matchup(league: League, team_a: &str, team_b: &str, seasons: Option<u32>, client: ApiClient) -> Result<Json<matchups::Matchup>, Status> {
+    return v1::matchup(league, team_a, team_b, seasons, client).await;
+}
+
+#[utoipa::path(
+    get, path = "/health/scrapers", tag = "health",
+    params(("team" = Option<String>, Query, description = "Team whose pages are checked, default tor"), ("league" = Option<String>, Query, description = "Default nba")),
+    responses((status = 200, description = "OK", body = scrape_health::ScraperHealth), (status = 404, description = "Unknown league"))
+)]
+#[get("/health/scrapers?<team>&<league>")]
+async fn scraper_health(team: Option<String>, league: Option<String>, _client: ApiClient) -> Option<Json<scrape_health::ScraperHealth>> {
+    let league = match league {
+        Some(league) => League::from_path(&league)?,
+        None => League::Nba
+    };
+    return Some(Json(scrape_health::run_scraper_health(league, &team.unwrap_or("tor".to_string())).await));
+}
+
+#[utoipa::path(
+    get, path = "/metrics", tag = "health",
+    responses((status = 200, description = "Request, ESPN fetch, parser and page cache metrics in the Prometheus text format", content_type = "text/plain", body = String))
+)]
+#[get("/metrics")]
+fn prometheus_metrics() -> (ContentType, String) {
+    return (ContentType::new("text", "plain").with_params([("version", "0.0.4"), ("charset", "utf-8")]), metrics::render());
+}
+
+#[utoipa::path(
+    get, path = "/admin/usage", tag = "admin",
+    responses((status = 200, description = "Requests per client since launch, keyed by API key name", body = BTreeMap<String, api_keys::Usage>), (status = 403, description = "Not an ops key"))
+)]
+#[get("/admin/usage")]
+fn api_usage(client: ApiClient, api_keys: &State<ApiKeys>) -> Result<Json<BTreeMap<String, api_keys::Usage>>, Status> {
+    if !client.ops {
+        return Err(Status::Forbidden);
+    }
+    return Ok(Json(api_keys.usage()));
+}
+
+#[utoipa::path(
+    get, path = "/admin/cache", tag = "admin",
+    params(("url" = Option<String>, Query, description = "Only entries whose URL starts with this")),
+    responses((status = 200, description = "Cached ESPN responses; empty when the page cache is off", body = Vec<page_cache::CacheEntry>), (status = 403, description = "Not an ops key"), (status = 500, description = "Reading the cache failed"))
+)]
+#[get("/admin/cache?<url>")]
+async fn cache_entries(url: Option<&str>, client: ApiClient) -> Result<Json<Vec<page_cache::CacheEntry>>, Status> {
+    if !client.ops {
+        return Err(Status::Forbidden);
+    }
+    let entries = match page_cache::cache() {
+        Some(cache) => tokio::task::spawn_blocking(move || cache.entries()).await.map_err(|_| Status::InternalServerError)?,
+        None => vec![]
+    };
+    return Ok(Json(entries.into_iter().filter(|entry| entry.url.starts_with(url.unwrap_or(""))).collect()));
+}
+
+#[utoipa::path(
+    delete, path = "/admin/cache", tag = "admin",
+    params(("url" = Option<String>, Query, description = "Only purge entries whose URL starts with this; everything goes without it")),
+    responses((status = 200, description = "How many entries were purged"), (status = 403, description = "Not an ops key"), (status = 500, description = "Purging the cache failed"))
+)]
+#[delete("/admin/cache?<url>")]
+async fn purge_cache(url: Option<&str>, client: ApiClient) -> Result<Json<Value>, Status> {
+    if !client.ops {
+        return Err(Status::Forbidden);
+    }
+    let prefix = url.map(|url| url.to_string());
+    let purged = match page_cache::cache() {
+        Some(cache) => tokio::task::spawn_blocking(move || cache.purge(prefix.as_deref())).await.map_err(|_| Status::InternalServerError)?,
+        None => 0
+    };
+    return Ok(Json(json!({"purged": purged})));
+}
+
+// Err(NotFound) when ESPN has no such page, e.g. an unknown team or game, and Err(BadGateway) when ESPN couldn't be
+// reached or answered with an error
+async fn get_page(url: String) -> Result<String, Status> {
+    let response = upstream::get(url).await.map_err(|_| Status::BadGateway)?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(Status::NotFound);
+    }
+    if !response.status().is_success() {
+        return Err(Status::BadGateway);
+    }
+    return response.text().await.map_err(|_| Status::BadGateway);
+}
+
+// Err(NotFound) when ESPN has no page for the team, no completed game on it, or no box score on the game's page
+pub async fn get_team_box_score(league: League, team_code: &str) -> Result<TeamBox, Status> {
+    if site_api::source(Resource::BoxScore) == DataSource::SiteApi {
+        if let Some(team_box) = site_api::get_team_box_score(league, team_code).await {
+            return Ok(team_box);
+        }
+    }
+    let team_page_html = get_page(league.url(&format!("team/_/name/{}", team_code))).await?;
+    let latest_game_id = find_latest_game_id(&team_page_html).ok_or(Status::NotFound)?; // 401307777
+    let boxscore_page_html = get_page(league.url(&format!("boxscore/_/gameId/{}", latest_game_id))).await?;
+    let home_or_away = get_orientation(&boxscore_page_html, team_code).ok_or(Status::NotFound)?;
+    return find_latest_game_box(&boxscore_page_html, home_or_away, league).ok_or(Status::NotFound);
+}
+
+// ranked below the API routes they share a shape with, e.g. /<league>/games/<game_id>/odds
+#[utoipa::path(
+    get, path = "/widgets/{league}/box/{team_code}", tag = "widgets",
+    security(("api_key" = []), ("api_key_query" = [])),
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("theme" = Option<String>, Query, description = "light (default) or dark"), ("accent" = Option<String>, Query, description = "Accent color as a bare hex value, e.g. ce1141")),
+    responses((status = 200, description = "Embeddable HTML", content_type = "text/html", body = String), (status = 404, description = "Unknown team, or no completed game yet"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/widgets/<league>/box/<team_code>?<theme>&<accent>", rank = 1)]
+async fn box_score_widget(league: League, team_code: &str, theme: Option<Theme>, accent: Option<&str>, _client: ApiClient) -> Result<widgets::Widget, Status> {
+    let team_box = get_team_box_score(league, team_code).await?;
+    return Ok(widgets::Widget(widgets::box_score(&team_box, widgets::palette(theme, accent))));
+}
+
+#[utoipa::path(
+    get, path = "/widgets/{league}/injuries/{team_code}", tag = "widgets",
+    security(("api_key" = []), ("api_key_query" = [])),
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("theme" = Option<String>, Query, description = "light (default) or dark"), ("accent" = Option<String>, Query, description = "Accent color as a bare hex value, e.g. ce1141")),
+    responses((status = 200, description = "Embeddable HTML", content_type = "text/html", body = String), (status = 404, description = "Unknown team"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/widgets/<league>/injuries/<team_code>?<theme>&<accent>", rank = 1)]
+async fn injury_card_widget(league: League, team_code: &str, theme: Option<Theme>, accent: Option<&str>, _client: ApiClient) -> Result<widgets::Widget, Status> {
+    let injuries = get_injuries_with_team_code(league).await?;
+    // teams missing from the injuries page have nobody hurt
+    let report = match injuries.into_iter().find(|report| report.team_code == team_code) {
+        Some(report) => report,
+        None => TeamInjuryReport {
+            team_code: team_code.to_string(),
+            team_name: fetch_teams(league).await?.into_iter().find(|team| team.id == team_code).ok_or(Status::NotFound)?.full_name,
+            injuries: vec![]
+        }
+    };
+    return Ok(widgets::Widget(widgets::injury_card(&report, league, widgets::palette(theme, accent))));
+}
+
+#[utoipa::path(
+    get, path = "/widgets/{league}/lineup/{team_code}", tag = "widgets",
+    security(("api_key" = []), ("api_key_query" = [])),
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("theme" = Option<String>, Query, description = "light (default) or dark"), ("accent" = Option<String>, Query, description = "Accent color as a bare hex value, e.g. ce1141")),
+    responses((status = 200, description = "Embeddable HTML", content_type = "text/html", body = String), (status = 404, description = "Unknown team, or no completed game yet"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/widgets/<league>/lineup/<team_code>?<theme>&<accent>", rank = 1)]
+async fn lineup_widget(league: League, team_code: &str, theme: Option<Theme>, accent: Option<&str>, _client: ApiClient) -> Result<widgets::Widget, Status> {
+    let team_box = get_team_box_score(league, team_code).await?;
+    let injuries = get_injuries_with_team_code(league).await?;
+    return Ok(widgets::Widget(widgets::lineup(team_code, &team_box, &injuries, widgets::palette(theme, accent))));
+}
+
+#[utoipa::path(
+    get, path = "/widgets/{league}/odds/{game_id}", tag = "widgets",
+    security(("api_key" = []), ("api_key_query" = [])),
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("game_id" = String, Path, description = "ESPN game id"), ("theme" = Option<String>, Query, description = "light (default) or dark"), ("accent" = Option<String>, Query, description = "Accent color as a bare hex value, e.g. ce1141")),
+    responses((status = 200, description = "Embeddable HTML, with a placeholder until the odds are posted", content_type = "text/html", body = String), (status = 404, description = "Unknown game"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/widgets/<league>/odds/<game_id>?<theme>&<accent>", rank = 1)]
+async fn odds_strip_widget(league: League, game_id: String, theme: Option<Theme>, accent: Option<&str>, _client: ApiClient) -> Result<widgets::Widget, Status> {
+    let game_odds = find_odds_for_game(league, game_id).await?;
+    return Ok(widgets::Widget(widgets::odds_strip(game_odds.as_ref(), league, widgets::palette(theme, accent))));
+}
+
+#[get("/openapi.json")]
+fn openapi_document() -> Json<Value> {
+    return Json(openapi::document());
+}
+
+#[get("/docs")]
+fn api_docs() -> content::RawHtml<&'static str> {
+    return content::RawHtml(openapi::REDOC_PAGE);
+}
+
+#[get("/openapi/schemas")]
+fn json_schemas() -> Json<Vec<String>> {
+    return Json(openapi::schema_names());
+}
+
+#[get("/openapi/schemas/<name>")]
+fn json_schema(name: &str) -> Option<Json<Value>> {
+    // `/openapi/schemas/TeamBox.json` and `/openapi/schemas/TeamBox` both work
+    return openapi::json_schema(name.strip_suffix(".json").unwrap_or(name)).map(Json);
+}
+
+#[post("/teams", rank = 2)]
+async fn teams(client: ApiClient) -> Result<Json<Vec<Team>>, Status> {
+    return v1::teams(League::Nba, client).await;
+}
+
+#[post("/<league>/teams", rank = 2)]
+async fn league_teams(league: League, client: ApiClient) -> Result<Json<Vec<Team>>, Status> {
+    return v1::teams(league, client).await;
+}
+
+#[get("/injuries", rank = 2)]
+async fn get_injuries(format: ExportFormat, client: ApiClient) -> Result<Exported<Vec<TeamInjuryReport>>, Status> {
+    return v1::injuries(League::Nba, format, client).await;
+}
+
+#[get("/<league>/injuries", rank = 2)]
+async fn get_league_injuries(league: League, format: ExportFormat, client: ApiClient) -> Result<Exported<Vec<TeamInjuryReport>>, Status> {
+    return v1::injuries(league, format, client).await;
+}
+
+
+fn get_team_code_from_logo_url(logo_url: String) -> String {
+    return logo_url.split("500/").collect::<Vec<&str>>()[1].split(".png").collect::<Vec<&str>>()[0].to_string();
+}
+
+pub async fn get_odds_for_game(league: League, game_id: String) -> Option<GameOdds> {
+    return find_odds_for_game(league, game_id).await.ok().flatten();
+}
+
+// Err(NotFound) when ESPN has no page for the game, Ok(None) when the odds aren't posted yet
+pub async fn find_odds_for_game(league: League, game_id: String) -> Result<Option<GameOdds>, Status> {
+    if site_api::source(Resource::Odds) == DataSource::SiteApi {
+        if let Some(game_odds) = site_api::get_odds_for_game(league, &game_id).await {
+            return Ok(game_odds);
+        }
+    }
+    let game_page_html = get_page(league.url(&format!("game/_/gameId/{}", game_id))).await?;
+    return Ok(get_odds_for_game_html(game_page_html));
+}
+
+// counted, since None for every game is how a change to ESPN's odds markup shows up
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_odds_for_game_html(html: String) -> Option<GameOdds> {
+    return metrics::parsed("get_odds_for_game_html", || parse_odds_for_game_html(html), |game_odds| game_odds.is_some());
+}
+
+fn parse_odds_for_game_html(html: String) -> Option<GameOdds> {
+    if let Some(game_odds) = embedded_json::parse(PageType::Game, &html, embedded_json::game_odds) {
+        return Some(game_odds);
+    }
+    let fragment = Html::parse_fragment(&html);
+    let away_img = tracked_select_optional(fragment.root_element(), "odds.away_logo").into_iter().next()?;
+    let away_team = get_team_code_from_logo_url(away_img.value().attr("src").unwrap().to_string());
+    let home_team = get_team_code_from_logo_url(tracked_select(fragment.root_element(), "odds.home_logo")[0].value().attr("src").unwrap().to_string());
+
+    let mut select = tracked_select(fragment.root_element(), "odds.rows").into_iter();
+    let spread = select.next();
+    let providers = odds::get_provider_lines_from_html(&html);
+    let mut game_odds = GameOdds {
+        home_team,
+        away_team,
+        home_spread: "".to_string(),
+        away_spread: "".to_string(),
+        home_moneyline: "".to_string(),
+        away_moneyline: "".to_string(),
+        over_under: "".to_string(),
+        numeric: None,
+        consensus: odds::consensus_line(&providers),
+        providers,
+        matchup_predictor: odds::get_matchup_predictor_from_html(&fragment)
+    };
+    if let Some(spread) = spread {
+        game_odds.away_spread = get_first_text_value(spread, "odds.away_value");
+        game_odds.home_spread = get_first_text_value(spread, "odds.home_value");
+    }
+    let moneyline = select.next();
+    if let Some(moneyline) = moneyline {
+        game_odds.away_moneyline = get_first_text_value(moneyline, "odds.away_value");
+        game_odds.home_moneyline = get_first_text_value(moneyline, "odds.home_value");
+    }
+    let over_under = select.next();
+    if let Some(over_under) = over_under {
+        game_odds.over_under = get_first_text_value(over_under, "odds.over_under");
+    }
+    game_odds.numeric = Some(odds::numeric_odds(&game_odds));
+    return Option::Some(game_odds);
+}
+
+#[test]
+fn get_odds_for_game_html_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html");
+    let odds = get_odds_for_game_html(contents.unwrap()).unwrap();
+    assert_eq!(odds.away_team, "phx");
+    assert_eq!(odds.home_team, "tor");
+    assert_eq!(odds.away_spread, "-4.0");
+    assert_eq!(odds.home_spread, "+4");
+    assert_eq!(odds.away_moneyline, "-180");
+    assert_eq!(odds.home_moneyline, "+155");
+    assert_eq!(odds.over_under, "223.0");
+    let numeric = odds.numeric.unwrap();
+    assert_eq!(numeric.favourite, Some("phx".to_string()));
+    assert_eq!(numeric.away.spread, Some(-4.0));
+    assert_eq!(numeric.home.spread, Some(4.0));
+    assert_eq!(numeric.away.moneyline, Some(-180));
+    assert_eq!(numeric.home.moneyline, Some(155));
+    assert_eq!(numeric.home.decimal, Some(2.55));
+    assert_eq!(numeric.home.fractional, Some("31/20".to_string()));
+    assert_eq!(numeric.over_under, Some(223.0));
+    assert_eq!(odds.providers.len(), 3);
+    let consensus = odds.consensus.unwrap();
+    assert_eq!((consensus.away_spread, consensus.home_moneyline, consensus.over_under), (Some(-4.5), Some(149), Some(223.5)));
+    assert_eq!(odds.matchup_predictor.unwrap().home_win_percentage, 50.2);
+}
+
+#[test]
+fn get_odds_for_game_html_when_no_odds_present_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds-not-found.html");
+    let odds = get_odds_for_game_html(contents.unwrap());
+    assert_eq!(odds.is_none(), true);
+}
+
+async fn get_previous_results(league: League, team_code: String) -> Result<Vec<GameResult>, Status> {
+    let team_page_html = get_page(league.url(&format!("team/_/name/{}", team_code))).await?;
+    return Ok(get_previous_results_from_team_page_html(team_page_html));
+}
+
+
+fn get_previous_results_from_team_page_html(html: String) -> Vec<GameResult> {
+    let mut game_results = get_completed_games_from_team_page_html(&html);
+    game_results.truncate(5);
+    return game_results;
+}
+
+// every completed game on the team page schedule, most recent first
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+fn get_completed_games_from_team_page_html(html: &str) -> Vec<GameResult> {
+    return metrics::parsed("get_completed_games_from_team_page_html", || parse_completed_games_from_team_page_html(html), |game_results| !game_results.is_empty());
+}
+
+fn parse_completed_games_from_team_page_html(html: &str) -> Vec<GameResult> {
+    if let Some(game_results) = embedded_json::parse(PageType::TeamPage, html, embedded_json::completed_games) {
+        return game_results;
+    }
+    let fragment = Html::parse_fragment(html);
+    let mut game_results = Vec::new();
+    for a in tracked_select(fragment.root_element(), "previous_results.games") {
+        // postponed games sit in the completed list without a score
+        if selectors::select_first(a, "previous_results.score").is_none() {
+            continue;
+        }
+        let href = a.value().attr("href").unwrap().to_string();
+        game_results.push(GameResult {
+            game_id: href.split("/").collect::<Vec<&str>>()[7].to_string(),
+            opponent: get_first_text_value(a, "previous_results.opponent"),
+            score: get_first_text_value(a, "previous_results.score"),
+            at_vs: get_first_text_value(a, "previous_results.at_vs"),
+            result: get_first_text_value(a, "previous_results.result"),
+            box_score_link: href.replace("/game/", "/boxscore/")
+        });
+    }
+    return game_results;
+}
+
+
+#[test]
+fn get_previous_games_test() {
+    let contents = std::fs::read_to_string("./test-data/brooklyn-home-page-for-previous-games.html");
+    let previous_games = get_previous_results_from_team_page_html(contents.unwrap());
+    assert_eq!(previous_games[0].opponent, "Jazz");
+    assert_eq!(previous_games[0].score, "125-102");
+    assert_eq!(previous_games[0].result, "L");
+    assert_eq!(previous_games[0].at_vs, "@");
+    assert_eq!(previous_games[0].box_score_link, "http://www.espn.com/nba/boxscore/_/gameId/401360612");
+    assert_eq!(previous_games[0].game_id, "401360612");
+    assert_eq!(previous_games.len(), 5);
+}
+
+pub async fn fetch_teams(league: League) -> Result<Vec<Team>, Status> {
+    if site_api::source(Resource::Teams) == DataSource::SiteApi {
+        if let Some(teams) = site_api::get_teams(league).await {
+            return Ok(teams);
+        }
+    }
+    return Ok(get_teams(get_page(league.url("teams")).await?, league));
+}
+
+async fn fetch_injuries(league: League) -> Result<Vec<TeamInjuryReport>, Status> {
+    if site_api::source(Resource::Injuries) == DataSource::SiteApi {
+        if let Some(team_injury_reports) = site_api::get_injuries(league).await {
+            return Ok(team_injury_reports);
+        }
+    }
+    return Ok(injuries(get_page(league.url("injuries")).await?));
+}
+
+pub async fn get_injuries_with_team_code(league: League) -> Result<Vec<TeamInjuryReport>, Status> {
+    let teams = fetch_teams(league).await?;
+    let team_injury_reports = fetch_injuries(league).await?;
+    let mut team_injury_reports_return = Vec::new();
+    for team in &teams {
+        for tir in &team_injury_reports {
+            if team.full_name == tir.team_name {
+                team_injury_reports_return.push(TeamInjuryReport {
+                    team_code: team.id.to_string(),
+                    team_name: tir.team_name.clone(),
+                    injuries: tir.injuries.to_owned()
+                });
+            }
+        }
+    }
+    return Ok(team_injury_reports_return);
+}
+
+
+fn legacy_routes() -> Vec<rocket::Route> {
+    return routes![box_score, previous_results, teams, league_teams, get_probable_lineups, get_injuries, get_league_injuries, game_odds, game_odds_history, team_ats, game_preview, matchup];
+}
+
+pub fn rocket() -> rocket::Rocket<rocket::Build> {
+    return custom(rocket::Config::figment());
+}
+
+// `rocket()` with its configuration supplied, e.g. the integration tests pointing `espn_base_url` at a mock server
+pub fn custom(figment: rocket::figment::Figment) -> rocket::Rocket<rocket::Build> {
+    rocket::custom(figment)
+        // first, so the other fairings' startup errors go through the subscriber
+        .attach(AdHoc::config::<logging::LoggingConfig>())
+        .attach(AdHoc::try_on_ignite("Logging", |rocket| async {
+            match logging::init(rocket.state::<logging::LoggingConfig>().unwrap()) {
+                Ok(()) => Ok(rocket),
+                Err(error) => {
+                    // there's no subscriber to report this through
+                    eprintln!("{}", json!({"level": "error", "event": "logging_rejected", "error": error}));
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(logging::RequestLog)
+        .attach(metrics::RequestMetrics)
+        .attach(AdHoc::config::<cors::CorsConfig>())
+        .attach(AdHoc::try_on_ignite("CORS", |rocket| async {
+            // a policy browsers would reject, e.g. credentials with a wildcard origin, stops launch
+            match cors::fairing(rocket.state::<cors::CorsConfig>().unwrap()) {
+                Ok(cors) => Ok(rocket.attach(cors)),
+                Err(error) => {
+                    tracing::error!(error = error.as_str(), "cors_rejected");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::config::<api_keys::ApiKeyConfig>())
+        .attach(AdHoc::try_on_ignite("API keys", |rocket| async {
+            match ApiKeys::new(rocket.state::<api_keys::ApiKeyConfig>().unwrap()) {
+                Ok(api_keys) => Ok(rocket.manage(api_keys)),
+                Err(error) => {
+                    tracing::error!(error = error.as_str(), "api_keys_rejected");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::config::<OddsHistoryConfig>())
+        .attach(AdHoc::try_on_ignite("Odds history", |rocket| async {
+            let config = rocket.state::<OddsHistoryConfig>().unwrap();
+            match OddsHistoryStore::open(PathBuf::from(&config.odds_history_path)) {
+                Ok(store) => Ok(rocket.manage(store)),
+                Err(error) => {
+                    tracing::error!(error = error.as_str(), "odds_history_rejected");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::config::<league::LeagueConfig>())
+        .attach(AdHoc::on_liftoff("Odds snapshots", |rocket| Box::pin(async move {
+            let store = rocket.state::<OddsHistoryStore>().unwrap().clone();
+            let interval = rocket.state::<OddsHistoryConfig>().unwrap().odds_snapshot_interval;
+            odds_history::spawn_snapshotter(store, interval, rocket.state::<league::LeagueConfig>().unwrap().leagues.clone());
+        })))
+        .attach(AdHoc::config::<embedded_json::ParserBackendConfig>())
+        .attach(AdHoc::on_ignite("Parser backends", |rocket| async {
+            embedded_json::configure(rocket.state::<embedded_json::ParserBackendConfig>().unwrap().parser_backends.clone());
+            rocket
+        }))
+        .attach(AdHoc::config::<page_cache::PageCacheConfig>())
+        .attach(AdHoc::try_on_ignite("Page cache", |rocket| async {
+            let config = rocket.state::<page_cache::PageCacheConfig>().unwrap();
+            if config.page_cache_enabled {
+                match page_cache::PageCache::open(PathBuf::from(&config.page_cache_path)) {
+                    Ok(cache) => page_cache::configure(cache),
+                    Err(error) => {
+                        tracing::error!(error = error.as_str(), "page_cache_rejected");
+                        return Err(rocket);
+                    }
+                }
+            }
+            Ok(rocket)
+        }))
+        .attach(AdHoc::config::<fixtures::FixtureRecorderConfig>())
+        .attach(AdHoc::try_on_ignite("Fixture recorder", |rocket| async {
+            if let Some(path) = &rocket.state::<fixtures::FixtureRecorderConfig>().unwrap().fixture_record_path {
+                match fixtures::FixtureRecorder::open(PathBuf::from(path)) {
+                    Ok(recorder) => fixtures::configure(recorder),
+                    Err(error) => {
+                        tracing::error!(error = error.as_str(), "fixture_recorder_rejected");
+                        return Err(rocket);
+                    }
+                }
+            }
+            Ok(rocket)
+        }))
+        .attach(AdHoc::config::<upstream::UpstreamConfig>())
+        .attach(AdHoc::on_ignite("Upstream client", |rocket| async {
+            upstream::configure(rocket.state::<upstream::UpstreamConfig>().unwrap().clone());
+            rocket
+        }))
+        .attach(AdHoc::config::<site_api::DataSourceConfig>())
+        .attach(AdHoc::on_ignite("Data sources", |rocket| async {
+            site_api::configure(rocket.state::<site_api::DataSourceConfig>().unwrap().data_sources.clone());
+            rocket
+        }))
+        .attach(AdHoc::config::<health::ReadinessConfig>())
+        .manage(health::Readiness::default())
+        .attach(AdHoc::on_liftoff("Team registry", |rocket| Box::pin(async move {
+            // /health/ready stays 503 until this has loaded
+            let retry_interval = rocket.state::<health::ReadinessConfig>().unwrap().team_registry_retry_interval;
+            for league in &rocket.state::<league::LeagueConfig>().unwrap().leagues {
+                health::spawn_team_registry_load(rocket.state::<health::Readiness>().unwrap().clone(), *league, retry_interval);
+            }
+        })))
+        .attach(AdHoc::config::<selectors::SelectorsConfig>())
+        .attach(AdHoc::try_on_ignite("Selectors", |rocket| async {
+            // without a file on disk the compiled-in selectors are used; a broken file stops launch
+            let path = PathBuf::from(&rocket.state::<selectors::SelectorsConfig>().unwrap().selectors_path);
+            if !path.exists() {
+                return Ok(rocket);
+            }
+            match selectors::load(&path) {
+                Ok(set) => {
+                    selectors::install(set);
+                    Ok(rocket)
+                }
+                Err(error) => {
+                    tracing::error!(path = %path.display(), error = error.as_str(), "selectors_rejected");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::on_liftoff("Selector reload", |rocket| Box::pin(async move {
+            let config = rocket.state::<selectors::SelectorsConfig>().unwrap();
+            selectors::watch(PathBuf::from(&config.selectors_path), config.selectors_reload_interval);
+        })))
+        .mount("/", logging::traced(legacy_routes()))
+        .mount("/v1", logging::traced(v1::routes()))
+        .mount("/v2", logging::traced(v2::routes()))
+        .mount("/", logging::traced(routes![scraper_health, prometheus_metrics, api_usage, cache_entries, purge_cache]))
+        .mount("/", logging::traced(health::routes()))
+        .mount("/", logging::traced(routes![box_score_widget, injury_card_widget, lineup_widget, odds_strip_widget]))
+        .mount("/", logging::traced(routes![openapi_document, api_docs, json_schemas, json_schema]))
+        .register("/", catchers![api_keys::too_many_requests])
+}
+
+#[rocket::async_test]
+async fn routes_do_not_collide_test() {
+    rocket().ignite().await.unwrap();
+}
+
+
+#[derive(strum_macros::Display, Debug)]
+#[allow(non_camel_case_types)]
+pub enum HomeOrAway {
+    home,
+    away,
+}
+
+// the flat fields are the line the page shows, the first sportsbook ESPN lists; the consensus has its own field
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(description = "The flat spread, moneyline and total fields are the line ESPN's game page shows, which is the first sportsbook it lists. ESPN's consensus line, the average across sportsbooks, is in consensus.")]
+pub struct GameOdds {
+    home_team: String,
+    away_team: String,
+    home_spread: String,
+    away_spread: String,
+    home_moneyline: String,
+    away_moneyline: String,
+    over_under: String,
+    numeric: Option<NumericOdds>,
+    #[serde(default)]
+    providers: Vec<ProviderLine>,
+    /// ESPN's consensus line, absent when the game has none
+    #[serde(default)]
+    consensus: Option<ProviderLine>,
+    matchup_predictor: Option<MatchupPredictor>
+}
+
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TeamBox {
+    pub overview: Overview,
+    pub player_records: Vec<Player>,
+    pub manager: HashMap<String, String>
+
+}
+// has of a hash
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Overview {
+    score: GameScore,
+    event: TwoTeams,
+    share_url: String
+}
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TwoTeams {
+    away_team: OrientedTeam,
+    home_team: OrientedTeam
+}
+
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Logos {
+    w72xh72: String
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrientedTeam {
+    logos: Logos,
+    id: String,
+    medium_name: String
+}
+
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PlayerInjury {
+    name: String,
+    date: String,
+    position: String,
+    status: String,
+    description: String,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TeamInjuryReport {
+    team_code: String,
+    team_name: String,
+    injuries: Vec<PlayerInjury>
+}
+
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PlayerBoxScore {
+    starter: bool,
+    first_initial_and_last_name: String,
+    player_id: String,
+    position: String,
+    minutes: String,
+    field_goals_made: String,
+    field_goals_attempted: String,
+    three_point_field_goals_made: String,
+    three_point_field_goals_attempted: String,
+    free_throws_made: String,
+    free_throws_attempted: String,
+    oreb: String,
+    dreb: String,
+    rebounds_total: String,
+    assists: String,
+    steals: String,
+    blocked_shots: String,
+    turnovers: String,
+    pf: String,
+    plus_minus: String,
+    points: String,
+    dnp: String,
+    headshots: HashMap<String, String>
+}
+
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionOptions {
+    position: String,
+    players: Vec<Player>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Team {
+    id: String,
+    full_name: String
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Player {
+    id: String,
+    alignment: String,
+    player: PlayerBoxScore
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GameScore {
+    away: TeamScore,
+    home: TeamScore
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TeamScore {
+    score: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProbableLineup {
+    team_code: String,
+    lineup_by_position: HashMap<String, Vec<Player>>,
+    injury_report: TeamInjuryReport,
+    previous_results: Vec<GameResult>
+
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpcomingLineups {
+    team: ProbableLineup,
+    opponent: ProbableLineup,
+    odds: Option<GameOdds>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GameResult {
+    game_id: String,
+    opponent: String,
+    result: String,
+    score: String,
+    box_score_link: String,
+    at_vs: String
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+fn find_upcoming_game_id_from_html(team_page_html: &str) -> Option<String> {
+    return metrics::parsed("find_upcoming_game_id_from_html", || parse_upcoming_game_id_from_html(team_page_html), |game_id| game_id.is_some());
+}
+
+fn parse_upcoming_game_id_from_html(team_page_html: &str) -> Option<String> {
+    if let Some(game_id) = embedded_json::parse(PageType::TeamPage, team_page_html, embedded_json::upcoming_game_id) {
+        return Some(game_id);
+    }
+    let fragment = Html::parse_fragment(team_page_html);
+    let a = tracked_select_optional(fragment.root_element(), "upcoming_game_id.game").into_iter().next()?;
+    return a.value().attr("href")?.split("/").nth(7).map(|game_id| game_id.to_string());
+}
+
+#[test]
+fn get_upcoming_game_id_test() {
+    let contents = std::fs::read_to_string("./test-data/okc-home-page-upcoming-game-id.html");
+    assert_eq!(find_upcoming_game_id_from_html(&contents.unwrap()), Some("401360620".to_string()));
+
+}
+
+fn get_upcoming_opponent_team_code(html: String, league: League, teams: &[Team]) -> String {
+    return find_upcoming_opponent_team_code(&html, league, teams).unwrap();
+}
+
+// None once the season is over and the schedule strip has no upcoming game
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+fn find_upcoming_opponent_team_code(html: &str, league: League, teams: &[Team]) -> Option<String> {
+    return metrics::parsed("find_upcoming_opponent_team_code", || parse_upcoming_opponent_team_code(html, league, teams), |team_code| team_code.is_some());
+}
+
+fn parse_upcoming_opponent_team_code(html: &str, league: League, teams: &[Team]) -> Option<String> {
+    if let Some(short_name) = embedded_json::parse(PageType::TeamPage, html, embedded_json::upcoming_opponent_name) {
+        return league::team_code_from_short_name(league, &short_name, teams);
+    }
+    let fragment = Html::parse_fragment(html);
+    let upcoming = tracked_select(fragment.root_element(), "upcoming_opponent.team").into_iter().next()?;
+    return league::team_code_from_short_name(league, &upcoming.inner_html(), teams);
+}
+
+fn get_latest_game_id(html: String) -> String {
+    return find_latest_game_id(&html).unwrap();
+}
+
+// None for a page without a schedule strip, e.g. what ESPN serves for a team code it doesn't know
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+fn find_latest_game_id(html: &str) -> Option<String> {
+    return metrics::parsed("find_latest_game_id", || parse_latest_game_id(html), |game_id| game_id.is_some());
+}
+
+fn parse_latest_game_id(html: &str) -> Option<String> {
+    if let Some(game_id) = embedded_json::parse(PageType::TeamPage, html, embedded_json::latest_game_id) {
+        return Some(game_id);
+    }
+    let fragment = Html::parse_fragment(html);
+    let completed = tracked_select(fragment.root_element(), "latest_game_id.completed").into_iter().next();
+    let live = tracked_select_optional(fragment.root_element(), "latest_game_id.live").into_iter().next();
+    let href = live.or(completed)?.value().attr("href")?;
+    let is_game_live = href.contains("=");
+    return match is_game_live {
+        true => href.split("=").nth(1).map(|game_id| game_id.to_string()),
+        false => href.split("/").nth(7).map(|game_id| game_id.to_string())
+    };
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+// None when the page has no game header, e.g. an error page or an empty body
+fn get_game_header(html: &str) -> Option<Overview> {
+    return metrics::parsed("get_game_header", || parse_game_header(html), |overview| overview.is_some());
+}
+
+fn parse_game_header(html: &str) -> Option<Overview> {
+    let fragment = Html::parse_fragment(html);
+    let side = |side_selector: &str| -> Option<(OrientedTeam, TeamScore)> {
+        let elem = tracked_select(fragment.root_element(), side_selector).into_iter().next()?;
+        let first = |selector: &str| tracked_select(elem, selector).into_iter().next();
+        let first_text = |selector: &str| Some(first(selector)?.text().next().unwrap_or("").trim().to_string());
+        let team = OrientedTeam {
+            logos: Logos { w72xh72: first("game_header.logo")?.value().attr("src")?.to_string() },
+            id: first("game_header.team_link")?.value().attr("href")?.split("/").nth(5)?.to_string(),
+            medium_name: first_text("game_header.name")?
+        };
+        return Some((team, TeamScore { score: first_text("game_header.score")? }));
+    };
+    let (home_oriented, home_score) = side("game_header.home")?;
+    let (away_oriented, away_score) = side("game_header.away")?;
+    let box_score_link = selectors::select_first(fragment.root_element(), "game_header.share_url")?.value().attr("href")?.to_string();
+    return Some(Overview {
+        share_url: box_score_link,
+        score: GameScore { away: away_score, home: home_score },
+        event: TwoTeams {
+            away_team: away_oriented,
+            home_team: home_oriented
+        }
+    });
+}
+
+// pulls the right-hand side of an inline `<name> = <value>;` script assignment
+fn get_script_assignment<'a>(html: &'a str, name: &str) -> Option<&'a str> {
+    let marker = format!("{} = ", name);
+    let start = html.find(&marker)? + marker.len();
+    return html[start..].lines().next().map(|value| value.trim().trim_end_matches(';'));
+}
+
+fn get_first_text_value(parent_element: ElementRef, selector: &str) -> String {
+    let vec = tracked_select(parent_element, selector)[0].text().collect::<Vec<_>>();
+    return match vec.len() {
+        0 => "".to_string(),
+        _=> vec[0].to_string().trim().to_string()
+    };
+}
+
+fn extract_team_code_from_a_tag(a_tag_selector: &str, parent_element: ElementRef) -> String {
+    tracked_select(parent_element, a_tag_selector)[0].value().attr("href").unwrap().split("/").collect::<Vec<&str>>()[5].to_string()
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_teams(html: String, league: League) -> Vec<Team> {
+    return metrics::parsed("get_teams", || parse_teams(html, league), |teams| !teams.is_empty());
+}
+
+fn parse_teams(html: String, league: League) -> Vec<Team> {
+    if let Some(teams) = embedded_json::parse(PageType::Teams, &html, |content| embedded_json::teams(content, league)) {
+        return teams;
+    }
+    let fragment = Html::parse_fragment(&html);
+    let team_links = tracked_select(fragment.root_element(), "teams.team_links");
+    let mut vec = Vec::new();
+
+    for team_link in team_links {
+        let a = selectors::select_first(team_link, "teams.link").unwrap();
+        let team_page_link = a.value().attr("href").unwrap().split("/").collect::<Vec<&str>>()[5].to_string();
+
+        let team_name_h2 = selectors::select_first(a, "teams.name").unwrap().text().collect::<Vec<_>>()[0].to_string();
+        vec.push(Team {
+            id: team_page_link.to_string(),
+            full_name: team_name_h2
+        });
+    }
+    return vec;
+
+
+}
+pub fn get_latest_game_box(html: &str, home_or_away: HomeOrAway, league: League) -> TeamBox {
+    return find_latest_game_box(html, home_or_away, league).unwrap();
+}
+
+// None when the page has no box score for the side, e.g. an error page or an empty body
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn find_latest_game_box(html: &str, home_or_away: HomeOrAway, league: League) -> Option<TeamBox> {
+    return metrics::parsed("find_latest_game_box", || parse_latest_game_box(html, home_or_away, league), |team_box| team_box.as_ref().is_some_and(|team_box| !team_box.player_records.is_empty()));
+}
+
+fn parse_latest_game_box(html: &str, home_or_away: HomeOrAway, league: League) -> Option<TeamBox> {
+    if let Some(team_box) = embedded_json::parse(PageType::BoxScore, html, |data| embedded_json::team_box(data, &home_or_away, league)) {
+        return Some(team_box);
+    }
+    let fragment = Html::parse_fragment(html);
+    let table_key = format!("box_score.{}_table", home_or_away);
+    let tbodys = tracked_select(fragment.root_element(), &table_key).into_iter().next()?;
+    let mut player_lines: Vec<Player> = vec![];
+
+    let mut player_count = 0;
+    for tr in tracked_select(tbodys, "box_score.rows") {
+        let mut player = PlayerBoxScore {
+            starter: false,
+            first_initial_and_last_name: "".to_string(),
+            player_id: "".to_string(),
+            position: "".to_string(),
+            minutes: "".to_string(),
+            field_goals_made: "".to_string(),
+            field_goals_attempted: "".to_string(),
+            three_point_field_goals_made: "".to_string(),
+            three_point_field_goals_attempted: "".to_string(),
+            free_throws_made: "".to_string(),
+            free_throws_attempted: "".to_string(),
+            oreb: "".to_string(),
+            dreb: "".to_string(),
+            rebounds_total: "".to_string(),
+            assists: "".to_string(),
+            steals: "".to_string(),
+            blocked_shots: "".to_string(),
+            turnovers: "".to_string(),
+            pf: "".to_string(),
+            plus_minus: "".to_string(),
+            points: "".to_string(),
+            dnp: "".to_string(),
+            headshots: HashMap::new()
+        };
+        let mut player_id = String::new();
+        let mut valid_row = false;
+        for td in selectors::select(tr, "box_score.cells") {
+            let name = td.value().attr("class").unwrap();
+            let td_contents = td.text().collect::<Vec<_>>();
+            let first_value = td_contents[0].to_string();
+            match name {
+                "name" => {
+
+                    let mut a_tag = selectors::select(td, "box_score.player_link").into_iter();
+                    valid_row = true;
+                    player.first_initial_and_last_name = first_value;
+                    player_id.push_str(a_tag.next().unwrap().value().attr("href").unwrap().split("/").collect::<Vec<&str>>()[7]);
+                    player.player_id = player_id.clone();
+                    let mut headshots: HashMap<String, String> = HashMap::new();
+                    headshots.insert("w192xh192".to_string(),league.headshot_url(&player_id));
+                    player.headshots = headshots;
+                    if td_contents.len() >= 3 {
+                        player.position = td_contents[2].to_string();
+                    }
+                }
+                "min" => player.minutes = first_value,
+                "fg" =>  {
+                    player.field_goals_made = first_value.split("-").collect::<Vec<&str>>()[0].to_string();
+                    player.field_goals_attempted = first_value.split("-").collect::<Vec<&str>>()[1].to_string();
+                }
+                "3pt" => {
+                    player.three_point_field_goals_made = first_value.split("-").collect::<Vec<&str>>()[0].to_string();
+                    player.three_point_field_goals_attempted = first_value.split("-").collect::<Vec<&str>>()[1].to_string();
+                }
+                "ft" => {
+                    player.free_throws_made = first_value.split("-").collect::<Vec<&str>>()[0].to_string();
+                    player.free_throws_attempted = first_value.split("-").collect::<Vec<&str>>()[1].to_string();
+                },
+                "oreb" => player.oreb = first_value,
+                "dreb" => player.dreb = first_value,
+                "reb" => player.rebounds_total = first_value,
+                "ast" => player.assists = first_value,
+                "stl" => player.steals = first_value,
+                "blk" => player.blocked_shots = first_value,
+                "to" => player.turnovers = first_value,
+                "pf" => player.pf = first_value,
+                "plusminus" => player.plus_minus = first_value,
+                "pts" => player.points = first_value,
+                "dnp" => player.dnp = first_value,
+                _ => ()
+            }
+        }
+        if valid_row {
+            player_count += 1;
+            player.starter = player_count <= 5;
+            player_lines.push(Player {
+                player,
+                id: player_id,
+                alignment: home_or_away.to_string()
+            });
+
+        }
+
+    }
+    let mut manager = HashMap::new();
+    manager.insert("image".to_string(), "https://i.imgur.com/QkbchIz.jpg".to_string());
+    manager.insert("name".to_string(), "Nick Nurse".to_string());
+    return Some(TeamBox {
+        overview: get_game_header(html)?,
+        player_records: player_lines,
+        manager
+    });
+}
+
+// None when the page has no team links to tell the sides apart, e.g. an error page or an empty body
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_orientation(html: &str, team_code: &str) -> Option<HomeOrAway> {
+    return metrics::parsed("get_orientation", || parse_orientation(html, team_code), |home_or_away| home_or_away.is_some());
+}
+
+fn parse_orientation(html: &str, team_code: &str) -> Option<HomeOrAway> {
+    if let Some(home_or_away) = embedded_json::parse(PageType::BoxScore, html, |data| embedded_json::orientation(data, team_code)) {
+        return Some(home_or_away);
+    }
+    let fragment = Html::parse_fragment(html);
+    let first_a_tag = tracked_select(fragment.root_element(), "orientation.team_link").into_iter().next()?;
+    let away_team = first_a_tag.value().attr("href")?.split("/").nth(5)?.to_string();
+    return Some(match *team_code == away_team {
+        true => HomeOrAway::away,
+        _ => HomeOrAway::home
+    });
+}
+
+#[test]
+fn get_teams_test() {
+    let contents = std::fs::read_to_string("./test-data/teams-page.html");
+    let teams = get_teams(contents.unwrap(), League::Nba);
+    assert_eq!(teams[0].id, String::from("bos"));
+    assert_eq!(teams[0].full_name, String::from("Boston Celtics"));
+    assert_eq!(teams[29].id, String::from("sa"));
+    assert_eq!(teams[29].full_name, String::from("San Antonio Spurs"));
+
+}
+
+
+#[test]
+fn get_latest_game_id_game_over_test() {
+    let contents = std::fs::read_to_string("./test-data/team-page-game-over.html");
+    assert_eq!(get_latest_game_id(contents.unwrap()), String::from("401360609"));
+}
+
+#[test]
+fn get_latest_game_id_live_game_test() {
+    let contents = std::fs::read_to_string("./test-data/team-page-live-game.html");
+    assert_eq!(get_latest_game_id(contents.unwrap()), String::from("401360629"));
+}
+
+#[test]
+fn get_latest_game_away_box_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-away-box.html");
+    let team_box = get_latest_game_box(&contents.unwrap(), HomeOrAway::away, League::Nba);
+    assert_eq!(team_box.player_records[0].player.first_initial_and_last_name, "P. Siakam");
+    assert_eq!(team_box.player_records[0].player.player_id, "3149673");
+    assert_eq!(team_box.player_records[0].player.free_throws_made, "5");
+    assert_eq!(team_box.player_records[0].player.free_throws_attempted, "7");
+    assert_eq!(team_box.player_records[9].player.first_initial_and_last_name, "J. Harris");
+    assert_eq!(team_box.player_records[11].player.first_initial_and_last_name, "A. Baynes");
+    assert_eq!(team_box.player_records[11].player.dnp, "DNP-COACH'S DECISION");
+    assert_eq!(team_box.overview.event.away_team.medium_name, "Raptors");
+    assert_eq!(team_box.overview.event.home_team.medium_name, "Lakers");
+    assert_eq!(team_box.overview.event.home_team.id, "lal");
+    assert_eq!(team_box.overview.event.away_team.id, "tor");
+    assert_eq!(team_box.overview.event.away_team.logos.w72xh72, "https://a.espncdn.com/combiner/i?img=/i/teamlogos/nba/500/tor.png&h=100&w=100");
+    assert_eq!(team_box.overview.event.home_team.logos.w72xh72, "https://a.espncdn.com/combiner/i?img=/i/teamlogos/nba/500/lal.png&h=100&w=100");
+    assert_eq!(team_box.overview.score.away.score, "121");
+    assert_eq!(team_box.overview.score.home.score, "114");
+    assert_eq!(team_box.overview.share_url, "https://www.espn.com/nba/boxscore/_/gameId/401307777");
+}
+
+#[test]
+fn get_latest_game_home_box_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html");
+    let team_box = get_latest_game_box(&contents.unwrap(), HomeOrAway::home, League::Nba);
+    assert_eq!(team_box.player_records[0].player.first_initial_and_last_name, "P. Siakam");
+    assert_eq!(team_box.player_records[0].player.starter, true);
+    assert_eq!(team_box.player_records[0].player.player_id, "3149673");
+    assert_eq!(team_box.player_records[6].player.first_initial_and_last_name, "Y. Watanabe");
+    assert_eq!(team_box.player_records[6].player.starter, false);
+    assert_eq!(team_box.player_records[11].player.first_initial_and_last_name, "A. Baynes");
+    assert_eq!(team_box.player_records[6].player.starter, false);
+    assert_eq!(team_box.player_records[11].player.dnp, "DNP-COACH'S DECISION");
+    assert_eq!(team_box.overview.event.away_team.medium_name, "Nets");
+    assert_eq!(team_box.overview.event.home_team.medium_name, "Raptors");
+    assert_eq!(team_box.overview.event.home_team.id, "tor");
+    assert_eq!(team_box.overview.event.away_team.id, "bkn");
+    assert_eq!(team_box.overview.event.away_team.logos.w72xh72, "https://a.espncdn.com/combiner/i?img=/i/teamlogos/nba/500/bkn.png&h=100&w=100");
+    assert_eq!(team_box.overview.event.home_team.logos.w72xh72, "https://a.espncdn.com/combiner/i?img=/i/teamlogos/nba/500/tor.png&h=100&w=100");
+    assert_eq!(team_box.overview.score.away.score, "116");
+    assert_eq!(team_box.overview.score.home.score, "103");
+    assert_eq!(team_box.overview.share_url, "https://www.espn.com/nba/boxscore/_/gameId/401307733");
+}
+
+#[test]
+fn get_orientation_home_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html");
+    assert_eq!(get_orientation(&contents.unwrap(), "tor").unwrap().to_string(), HomeOrAway::home.to_string());
+}
+
+#[test]
+fn get_orientation_away_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-away-box.html");
+    assert_eq!(get_orientation(&contents.unwrap(), "tor").unwrap().to_string(), HomeOrAway::away.to_string());
+    assert_eq!(get_orientation("", "tor").is_none(), true);
+}
+
+#[test]
+fn get_upcoming_opponent_team_code_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-team-page-upcoming-opponent.html");
+    assert_eq!(get_upcoming_opponent_team_code(contents.unwrap(), League::Nba, &[]).to_string(), "atl".to_string());
+}
+
+// the lineup implied by a saved box score page, without the injury report or previous results that need more fetches.
+// None when the page has no box score
+pub fn get_probable_lineup_from_box_score_html(html: &str, team_code: &str, league: League) -> Option<HashMap<String, Vec<Player>>> {
+    let team_box = find_latest_game_box(html, get_orientation(html, team_code)?, league)?;
+    return Some(probable_lineups(&team_box.player_records));
+}
+
+fn probable_lineups(players: &[Player]) -> HashMap<String, Vec<Player>> {
+    let mut by_position = HashMap::new();
+    let mut duplicates  =  Vec::new();
+    for  (idx, e) in players.iter().enumerate() {
+        let position = match e.player.position.as_ref() {
+            "F" => "PF".to_string(),
+            "G" => "SG".to_string(),
+            _ => e.player.position.to_string()
+        };
+        // if duplicate position in starters, store it
+        if idx < 5 {
+            if !by_position.contains_key(&position) {
+                let v = vec![blank_player(e.player.first_initial_and_last_name.to_string(), position.to_string(), e.player.starter)];
+                by_position.insert(position.to_string(), v);
+
+            } else {
+                duplicates.push(blank_player(e.player.first_initial_and_last_name.to_string(), position.to_string(), e.player.starter));
+            }
+        }
+        // distribute duplicates
+        if idx == 4 {
+            for pos in ["PG", "SG", "PF", "C", "SF"].iter() {
+                by_position.entry(pos.to_string()).or_insert_with(|| vec![duplicates.pop().unwrap()]);
+            }
+        }
+        // fill out remaining roster
+        if idx >= 5 {
+            by_position.entry(position.to_string()).or_insert(Vec::new()).push(
+                blank_player(e.player.first_initial_and_last_name.to_string(), position.to_string(), e.player.starter)
+            )
+        }
+    }
+    return by_position;
+}
+
+#[test]
+fn probable_lineups_starting_five_has_missing_sf_test() {
+    let players = vec![
+        blank_player("PF1".to_string(), "PF".to_string(), true),
+        blank_player("SF1".to_string(), "PF".to_string(), true),
+        blank_player("C1".to_string(), "C".to_string(), true),
+        blank_player("PG1".to_string(), "PG".to_string(), true),
+        blank_player("SG1".to_string(), "SG".to_string(), true),
+    ];
+    let lineup = probable_lineups(&players);
+
+    // starters must be on each
+    assert_eq!(lineup.get("PF").unwrap()[0].player.first_initial_and_last_name, "PF1");
+    assert_eq!(lineup.get("SF").unwrap()[0].player.first_initial_and_last_name, "SF1");
+    assert_eq!(lineup.get("C").unwrap()[0].player.first_initial_and_last_name, "C1");
+    assert_eq!(lineup.get("PG").unwrap()[0].player.first_initial_and_last_name, "PG1");
+    assert_eq!(lineup.get("SG").unwrap()[0].player.first_initial_and_last_name, "SG1");
+}
+
+
+#[test]
+fn four_positions_distributed_test() {
+    // "PG", "SG", "PF", "C", "SF"
+    let players = vec![
+        blank_player("PF1".to_string(), "PF".to_string(), true),
+        blank_player("SF1".to_string(), "PF".to_string(), true),
+        blank_player("C1".to_string(), "PF".to_string(), true),
+        blank_player("SG1".to_string(), "PF".to_string(), true),
+        blank_player("PG1".to_string(), "PF".to_string(), true),
+    ];
+    let lineup = probable_lineups(&players);
+
+    // starters must be on each
+    assert_eq!(lineup.get("PF").unwrap()[0].player.first_initial_and_last_name, "PF1");
+    assert_eq!(lineup.get("PG").unwrap()[0].player.first_initial_and_last_name, "PG1");
+    assert_eq!(lineup.get("SG").unwrap()[0].player.first_initial_and_last_name, "SG1");
+    assert_eq!(lineup.get("C").unwrap()[0].player.first_initial_and_last_name, "C1");
+    assert_eq!(lineup.get("SF").unwrap()[0].player.first_initial_and_last_name, "SF1");
+}
+
+
+// shared by the injuries page and the site API feed, so both sources describe an injury the same way
+pub fn injury_description(description: &str) -> String {
+    return description.replace("Blake Murphy of The Athletic", "Blake Murphy of SportsNet");
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn injuries(html: String) -> Vec<TeamInjuryReport> {
+    return metrics::parsed("injuries", || parse_injuries(html), |reports| !reports.is_empty());
+}
+
+fn parse_injuries(html: String) -> Vec<TeamInjuryReport> {
+    let fragment = Html::parse_fragment(&html);
+    // let description = get_first_text_value(row, &Selector::parse("injuries__teamName").unwrap());
+    let mut team_injury_reports = Vec::new();
+    for div in tracked_select(fragment.root_element(), "injuries.teams") {
+        let mut team_injury_report = TeamInjuryReport {
+            team_code: "".to_string(),
+            team_name: "".to_string(),
+            injuries: vec![]
+        };
+        team_injury_report.team_name = get_first_text_value(div, "injuries.team_name");
+        // a team with nobody hurt has no rows
+        for row in tracked_select_optional(div, "injuries.rows") {
+            team_injury_report.injuries.push(PlayerInjury {
+                name: get_first_text_value(row, "injuries.name"),
+                date: get_first_text_value(row, "injuries.date"),
+                position: get_first_text_value(row, "injuries.position"),
+                status: get_first_text_value(row, "injuries.status"),
+                description: injury_description(&get_first_text_value(row, "injuries.description"))
+            });
+        };
+        team_injury_reports.push(team_injury_report);
+    }
+    return team_injury_reports;
+}
+
+#[test]
+fn injuries_test() {
+    let contents = std::fs::read_to_string("./test-data/injuries.html");
+    let team_injury_reports = injuries(contents.unwrap());
+    assert_eq!(team_injury_reports[0].team_name, "Atlanta Hawks");
+    assert_eq!(team_injury_reports[1].team_name, "Boston Celtics");
+    assert_eq!(team_injury_reports[1].injuries[0].name, "Brodric Thomas");
+    assert_eq!(team_injury_reports[1].injuries[0].position, "G");
+    assert_eq!(team_injury_reports[1].injuries[0].date, "Jan 9");
+    assert_eq!(team_injury_reports[1].injuries[0].description, "Thomas (back) is listed as probable for Monday's game against the Pacers.");
+    assert_eq!(team_injury_reports[4].injuries.len(), 4);
+    assert_eq!(team_injury_reports.len(), 30);
+    assert_eq!(team_injury_reports[29].team_name, "Washington Wizards");
+}
+
+#[test]
+fn probable_lineups_test() {
+    let players = vec![
+        blank_player("PF1".to_string(), "PF".to_string(), true),
+        blank_player("SF1".to_string(), "SF".to_string(), true),
+        blank_player("C1".to_string(), "C".to_string(), true),
+        blank_player("PG1".to_string(), "PG".to_string(), true),
+        blank_player("SG1".to_string(), "SG".to_string(), true),
+        blank_player("PF2".to_string(), "PF".to_string(), false),
+        blank_player("SF2".to_string(), "SF".to_string(), false),
+        blank_player("C2".to_string(), "C".to_string(), false),
+        blank_player("PG2".to_string(), "PG".to_string(), false),
+        blank_player("SG2".to_string(), "SG".to_string(), false),
+        blank_player("PF3".to_string(), "PF".to_string(), false),
+        blank_player("SF3".to_string(), "SF".to_string(), false),
+        blank_player("PF4".to_string(), "F".to_string(), false),
+        blank_player("PG3".to_string(), "PG".to_string(), false),
+        blank_player("SG3".to_string(), "SG".to_string(), false),
+        blank_player("SG4".to_string(), "SG".to_string(), false),
+        blank_player("SG5".to_string(), "G".to_string(), false)
+    ];
+    let lineup = probable_lineups(&players);
+
+
+
+    // starters must be on each
+    assert_eq!(lineup.get("PF").unwrap()[0].player.first_initial_and_last_name, "PF1");
+    assert_eq!(lineup.get("PF").unwrap()[1].player.first_initial_and_last_name, "PF2");
+    assert_eq!(lineup.get("PF").unwrap()[2].player.first_initial_and_last_name, "PF3");
+    assert_eq!(lineup.get("PF").unwrap()[3].player.first_initial_and_last_name, "PF4");
+    assert_eq!(lineup.get("PF").unwrap().len(), 4);
+
+
+    assert_eq!(lineup.get("SF").unwrap()[0].player.first_initial_and_last_name, "SF1");
+    assert_eq!(lineup.get("SF").unwrap()[1].player.first_initial_and_last_name, "SF2");
+    assert_eq!(lineup.get("SF").unwrap()[2].player.first_initial_and_last_name, "SF3");
+    assert_eq!(lineup.get("SF").unwrap().len(), 3);
+
+    assert_eq!(lineup.get("PG").unwrap()[0].player.first_initial_and_last_name, "PG1");
+    assert_eq!(lineup.get("PG").unwrap()[1].player.first_initial_and_last_name, "PG2");
+    assert_eq!(lineup.get("PG").unwrap()[2].player.first_initial_and_last_name, "PG3");
+    assert_eq!(lineup.get("PG").unwrap().len(), 3);
+
+    assert_eq!(lineup.get("C").unwrap()[0].player.first_initial_and_last_name, "C1");
+    assert_eq!(lineup.get("C").unwrap()[1].player.first_initial_and_last_name, "C2");
+    assert_eq!(lineup.get("C").unwrap().len(), 2);
+
+    assert_eq!(lineup.get("SG").unwrap()[0].player.first_initial_and_last_name, "SG1");
+    assert_eq!(lineup.get("SG").unwrap()[1].player.first_initial_and_last_name, "SG2");
+    assert_eq!(lineup.get("SG").unwrap()[2].player.first_initial_and_last_name, "SG3");
+    assert_eq!(lineup.get("SG").unwrap()[3].player.first_initial_and_last_name, "SG4");
+    assert_eq!(lineup.get("SG").unwrap()[4].player.first_initial_and_last_name, "SG5");
+    assert_eq!(lineup.get("SG").unwrap().len(), 5);
+}
+
+fn blank_player(name: String, position: String, starter: bool) -> Player {
+    return Player {
+        id: name.to_string(),
+        alignment: "".to_string(),
+        player: PlayerBoxScore {
+            starter,
+            first_initial_and_last_name: name.to_string(),
+            player_id: "".to_string(),
+            position: position.to_string(),
+            minutes: "".to_string(),
+            field_goals_made: "".to_string(),
+            field_goals_attempted: "".to_string(),
+            three_point_field_goals_made: "".to_string(),
+            three_point_field_goals_attempted: "".to_string(),
+            free_throws_made: "".to_string(),
+            free_throws_attempted: "".to_string(),
+            oreb: "".to_string(),
+            dreb: "".to_string(),
+            rebounds_total: "".to_string(),
+            assists: "".to_string(),
+            steals: "".to_string(),
+            blocked_shots: "".to_string(),
+            turnovers: "".to_string(),
+            pf: "".to_string(),
+            plus_minus: "".to_string(),
+            points: "".to_string(),
+            dnp: "".to_string(),
+            headshots: HashMap::new()
+        }
+    };
+}
@@ -0,0 +1,148 @@
+use std::time::Instant;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::route::{Handler, Outcome, Route};
+use rocket::{Data, Request, Response};
+use serde::{Serialize, Deserialize};
+use tracing::Instrument;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Json,
+    Text
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggingConfig {
+    // an EnvFilter directive, e.g. "info" or "info,qr::upstream=debug"; parser spans, with their selector counts, are at debug
+    #[serde(default = "default_log_filter")]
+    pub log_filter: String,
+    #[serde(default = "default_log_format")]
+    pub log_format: LogFormat
+}
+
+fn default_log_filter() -> String {
+    return "info".to_string();
+}
+
+fn default_log_format() -> LogFormat {
+    return LogFormat::Json;
+}
+
+// installs the process-wide subscriber. every span logs when it closes, with how long it was busy and idle, so a slow
+// request shows which of its fetches or parsers took the time. a second call, e.g. another Rocket in the same test
+// binary, keeps the first subscriber
+pub fn init(config: &LoggingConfig) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&config.log_filter).map_err(|error| format!("invalid log_filter {:?}: {}", config.log_filter, error))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr);
+    let _ = match config.log_format {
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).with_span_list(true).try_init(),
+        LogFormat::Text => builder.try_init()
+    };
+    return Ok(());
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+struct Started(Instant);
+
+// a caller's own X-Request-Id is kept so their logs and ours line up, as long as it's short and plain
+fn request_id(header: Option<&str>) -> String {
+    return match header {
+        Some(id) if !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') => id.to_string(),
+        _ => format!("{:016x}", rand::random::<u64>())
+    };
+}
+
+// the query string as logged: anything that looks like a credential keeps its name but not its value, e.g.
+// "format=csv&api_key=***"
+fn masked_query(query: Option<&str>) -> String {
+    return query.unwrap_or("").split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        let name = pair.split('=').next().unwrap_or("");
+        let lowercase = name.to_lowercase();
+        match ["key", "token", "secret", "password"].iter().any(|word| lowercase.contains(word)) {
+            true => format!("{}=***", name),
+            false => pair.to_string()
+        }
+    }).collect::<Vec<String>>().join("&");
+}
+
+fn route_name<'r>(request: &'r Request<'_>) -> &'r str {
+    return request.route().and_then(|route| route.name.as_deref()).unwrap_or("unknown");
+}
+
+// gives every incoming call an id, echoes it back in X-Request-Id and logs the outcome once the response is ready
+pub struct RequestLog;
+
+#[rocket::async_trait]
+impl Fairing for RequestLog {
+    fn info(&self) -> Info {
+        return Info { name: "Request log", kind: Kind::Request | Kind::Response };
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let id = request_id(request.headers().get_one("X-Request-Id"));
+        request.local_cache(|| RequestId(id));
+        request.local_cache(|| Started(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let RequestId(id) = request.local_cache(|| RequestId(request_id(None)));
+        let Started(started) = request.local_cache(|| Started(Instant::now()));
+        response.set_header(Header::new("X-Request-Id", id.to_string()));
+        tracing::info!(
+            request_id = id.as_str(),
+            method = %request.method(),
+            path = %request.uri().path(),
+            query = masked_query(request.uri().query().map(|query| query.as_str())).as_str(),
+            route = route_name(request),
+            status = response.status().code,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "request_completed"
+        );
+    }
+}
+
+// runs a route's handler inside a `request` span, so the fetches and parsers it sets off carry its request id
+#[derive(Clone)]
+struct Traced(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for Traced {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        let RequestId(id) = request.local_cache(|| RequestId(request_id(None)));
+        let span = tracing::info_span!("request", request_id = id.as_str(), method = %request.method(), path = %request.uri().path(), query = masked_query(request.uri().query().map(|query| query.as_str())).as_str(), route = route_name(request));
+        return self.0.handle(request, data).instrument(span).await;
+    }
+}
+
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    return routes.into_iter().map(|mut route| {
+        route.handler = Box::new(Traced(route.handler.clone()));
+        route
+    }).collect();
+}
+
+#[test]
+fn request_id_test() {
+    assert_eq!(request_id(Some("lb-7f3a_2")), "lb-7f3a_2");
+    assert_eq!(request_id(None).len(), 16);
+    assert_eq!(request_id(Some("")).len(), 16);
+    assert_eq!(request_id(Some("has spaces; and=more")).len(), 16);
+    assert_eq!(request_id(Some(&"a".repeat(65))).len(), 16);
+}
+
+#[test]
+fn masked_query_test() {
+    assert_eq!(masked_query(None), "");
+    assert_eq!(masked_query(Some("format=csv&last=5")), "format=csv&last=5");
+    assert_eq!(masked_query(Some("format=csv&api_key=partner-key&Access_Token=abc")), "format=csv&api_key=***&Access_Token=***");
+}
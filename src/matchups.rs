@@ -0,0 +1,276 @@
+use chrono::{DateTime, Datelike, Utc};
+use rocket::http::Status;
+use scraper::Html;
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::{HomeOrAway, Player, get_game_header, get_page, find_latest_game_box};
+use crate::league::League;
+use crate::preview::{get_season_series_from_html, get_venue_from_html};
+use crate::scrape_health::{tracked_select, tracked_select_optional};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledGame {
+    pub game_id: String,
+    pub opponent: String
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TopScorer {
+    team_code: String,
+    name: String,
+    player_id: String,
+    points: u32
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Meeting {
+    game_id: String,
+    date: Option<String>,
+    venue: Option<String>,
+    away_team: String,
+    home_team: String,
+    away_score: u32,
+    home_score: u32,
+    winner: String,
+    top_scorers: Vec<TopScorer>
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct MatchupRecord {
+    team_code: String,
+    wins: u32,
+    losses: u32,
+    home_wins: u32,
+    home_losses: u32,
+    away_wins: u32,
+    away_losses: u32,
+    points_per_game: Option<f64>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Matchup {
+    seasons: Vec<i32>,
+    meetings: Vec<Meeting>,
+    team_a: MatchupRecord,
+    team_b: MatchupRecord
+}
+
+// ESPN labels a season by the year it ends in, so October onwards belongs to next year's NBA season;
+// the WNBA plays within a calendar year
+pub fn current_season(league: League, today: DateTime<Utc>) -> i32 {
+    return match league != League::Wnba && today.month() >= 10 {
+        true => today.year() + 1,
+        false => today.year()
+    };
+}
+
+pub fn team_code_from_href(href: &str) -> Option<String> {
+    let segments = href.split("/").collect::<Vec<&str>>();
+    let index = segments.iter().position(|segment| *segment == "name")?;
+    return segments.get(index + 1).map(|code| code.to_string());
+}
+
+// a schedule row links to both the opponent's team page and the game itself
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_games_from_schedule_html(html: &str) -> Vec<ScheduledGame> {
+    return crate::metrics::parsed("get_games_from_schedule_html", || parse_games_from_schedule_html(html), |games| !games.is_empty());
+}
+
+fn parse_games_from_schedule_html(html: &str) -> Vec<ScheduledGame> {
+    let fragment = Html::parse_document(html);
+    let mut games = Vec::new();
+    for tr in tracked_select(fragment.root_element(), "schedule.rows") {
+        // header and postponed-game rows have no links
+        let opponent = tracked_select_optional(tr, "schedule.team_link").into_iter().next()
+            .and_then(|a| a.value().attr("href"))
+            .and_then(team_code_from_href);
+        let game_id = tracked_select_optional(tr, "schedule.game_link").into_iter().next()
+            .and_then(|a| a.value().attr("href"))
+            .and_then(|href| href.split("gameId/").nth(1))
+            .map(|id| id.split("/").next().unwrap_or("").to_string());
+        if let (Some(opponent), Some(game_id)) = (opponent, game_id) {
+            let game = ScheduledGame { game_id, opponent };
+            if !games.contains(&game) {
+                games.push(game);
+            }
+        }
+    }
+    return games;
+}
+
+fn top_scorer(team_code: &str, players: &[Player]) -> Option<TopScorer> {
+    let player = players.iter().max_by_key(|p| p.player.points.parse::<u32>().unwrap_or(0))?;
+    return Some(TopScorer {
+        team_code: team_code.to_string(),
+        name: player.player.first_initial_and_last_name.to_string(),
+        player_id: player.player.player_id.to_string(),
+        points: player.player.points.parse::<u32>().unwrap_or(0)
+    });
+}
+
+// None until the game has a final score, or when the box score page isn't one
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_meeting_from_box_score_html(league: League, game_id: &str, box_score_html: &str, game_page_html: Option<&str>) -> Option<Meeting> {
+    return crate::metrics::parsed("get_meeting_from_box_score_html", || parse_meeting_from_box_score_html(league, game_id, box_score_html, game_page_html), |meeting| meeting.is_some());
+}
+
+fn parse_meeting_from_box_score_html(league: League, game_id: &str, box_score_html: &str, game_page_html: Option<&str>) -> Option<Meeting> {
+    let overview = get_game_header(box_score_html)?;
+    let away_score = overview.score.away.score.parse::<u32>().ok()?;
+    let home_score = overview.score.home.score.parse::<u32>().ok()?;
+    let away_team = overview.event.away_team.id;
+    let home_team = overview.event.home_team.id;
+    let away_box = find_latest_game_box(box_score_html, HomeOrAway::away, league)?;
+    let home_box = find_latest_game_box(box_score_html, HomeOrAway::home, league)?;
+    let date = get_season_series_from_html(box_score_html).into_iter()
+        .find(|game| game.game_id == game_id)
+        .and_then(|game| game.date);
+    return Some(Meeting {
+        game_id: game_id.to_string(),
+        date,
+        venue: game_page_html.and_then(get_venue_from_html),
+        winner: match away_score > home_score {
+            true => away_team.to_string(),
+            false => home_team.to_string()
+        },
+        top_scorers: vec![top_scorer(&away_team, &away_box.player_records), top_scorer(&home_team, &home_box.player_records)]
+            .into_iter().flatten().collect(),
+        away_team,
+        home_team,
+        away_score,
+        home_score
+    });
+}
+
+fn record(team_code: &str, meetings: &[Meeting]) -> MatchupRecord {
+    let mut record = MatchupRecord { team_code: team_code.to_string(), ..Default::default() };
+    let mut points = 0;
+    for meeting in meetings {
+        let at_home = meeting.home_team == team_code;
+        let won = meeting.winner == team_code;
+        points += if at_home { meeting.home_score } else { meeting.away_score };
+        match (won, at_home) {
+            (true, true) => { record.wins += 1; record.home_wins += 1 }
+            (true, false) => { record.wins += 1; record.away_wins += 1 }
+            (false, true) => { record.losses += 1; record.home_losses += 1 }
+            (false, false) => { record.losses += 1; record.away_losses += 1 }
+        }
+    }
+    if !meetings.is_empty() {
+        record.points_per_game = Some((points as f64 / meetings.len() as f64 * 10.0).round() / 10.0);
+    }
+    return record;
+}
+
+pub fn matchup(team_a: &str, team_b: &str, seasons: Vec<i32>, mut meetings: Vec<Meeting>) -> Matchup {
+    meetings.sort_by(|a, b| b.date.cmp(&a.date));
+    return Matchup {
+        seasons,
+        team_a: record(team_a, &meetings),
+        team_b: record(team_b, &meetings),
+        meetings
+    };
+}
+
+// each season costs a schedule fetch plus two more per meeting, so a request looks back this far at most
+pub const MAX_SEASONS: u32 = 5;
+
+// Err(NotFound) for an unknown team and Err(BadGateway) when ESPN fails. a season without a schedule page, e.g. one
+// from before a team joined the league, is skipped
+pub async fn get_matchup(league: League, team_a: &str, team_b: &str, seasons: u32) -> Result<Matchup, Status> {
+    // team_a's schedule only links team_b's games, so an unknown team_b would otherwise just have no meetings
+    get_page(league.url(&format!("team/_/name/{}", team_b))).await?;
+    let current = current_season(league, Utc::now());
+    let seasons = (0..seasons.clamp(1, MAX_SEASONS) as i32).map(|offset| current - offset).collect::<Vec<i32>>();
+    let mut schedules_found = 0;
+    let mut meetings: Vec<Meeting> = Vec::new();
+    for season in &seasons {
+        let schedule_html = match get_page(league.url(&format!("team/schedule/_/name/{}/season/{}", team_a, season))).await {
+            Ok(html) => html,
+            Err(status) if status == Status::NotFound => continue,
+            Err(status) => return Err(status)
+        };
+        schedules_found += 1;
+        for game in get_games_from_schedule_html(&schedule_html).into_iter().filter(|game| game.opponent == team_b) {
+            // ESPN serves the latest schedule for a season that hasn't started, so a game can be listed twice
+            if meetings.iter().any(|meeting| meeting.game_id == game.game_id) {
+                continue;
+            }
+            let box_score_html = match get_page(league.url(&format!("boxscore/_/gameId/{}", game.game_id))).await {
+                Ok(html) => html,
+                Err(status) if status == Status::NotFound => continue,
+                Err(status) => return Err(status)
+            };
+            let game_page_html = match get_page(league.url(&format!("game/_/gameId/{}", game.game_id))).await {
+                Ok(html) => Some(html),
+                Err(status) if status == Status::NotFound => None,
+                Err(status) => return Err(status)
+            };
+            if let Some(meeting) = get_meeting_from_box_score_html(league, &game.game_id, &box_score_html, game_page_html.as_deref()) {
+                meetings.push(meeting);
+            }
+        }
+    }
+    if schedules_found == 0 {
+        return Err(Status::NotFound);
+    }
+    return Ok(matchup(team_a, team_b, seasons, meetings));
+}
+
+#[test]
+fn current_season_test() {
+    let october = "2025-10-21T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    let may = "2025-05-16T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    assert_eq!(current_season(League::Nba, october), 2026);
+    assert_eq!(current_season(League::Nba, may), 2025);
+    // the WNBA's playoffs run into October, still the same season
+    assert_eq!(current_season(League::Wnba, october), 2025);
+}
+
+#[test]
+fn get_games_from_schedule_html_test() {
+    let html = r#"<table><tbody>
+        <tr class="Table__TR"><td><span>Tue, Apr 27</span></td>
+            <td><span>vs</span><a href="/nba/team/_/name/bkn/brooklyn-nets">Brooklyn</a></td>
+            <td><span>L</span><a href="https://www.espn.com/nba/game/_/gameId/401307733">116-103</a></td></tr>
+        <tr class="Table__TR"><td><span>Thu, Apr 29</span></td>
+            <td><span>@</span><a href="https://www.espn.com/nba/team/_/name/lal/los-angeles-lakers">Los Angeles</a></td>
+            <td><span>W</span><a href="https://www.espn.com/nba/game/_/gameId/401307777">121-114</a></td></tr>
+        <tr class="Table__TR"><td>DATE</td><td>OPPONENT</td><td>RESULT</td></tr>
+    </tbody></table>"#;
+    let games = get_games_from_schedule_html(html);
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0], ScheduledGame { game_id: "401307733".to_string(), opponent: "bkn".to_string() });
+    assert_eq!(games[1].opponent, "lal");
+
+    // a page without the schedule table shows up in /health/scrapers
+    let (health, games) = crate::scrape_health::check_parser("schedule", "empty.html", || get_games_from_schedule_html("<html><body></body></html>"), |games| !games.is_empty());
+    assert_eq!(games.unwrap().is_empty(), true);
+    assert_eq!(health.failing_selectors.len(), 1);
+}
+
+#[test]
+fn get_meeting_from_box_score_html_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html").unwrap();
+    let meeting = get_meeting_from_box_score_html(League::Nba, "401307733", &contents, None).unwrap();
+    assert_eq!(meeting.away_team, "bkn");
+    assert_eq!(meeting.home_team, "tor");
+    assert_eq!(meeting.away_score, 116);
+    assert_eq!(meeting.home_score, 103);
+    assert_eq!(meeting.winner, "bkn");
+    assert_eq!(meeting.date, Some("2021-04-27T23:30:00Z".to_string()));
+    assert_eq!(meeting.top_scorers.len(), 2);
+    assert_eq!(meeting.top_scorers[1].team_code, "tor");
+
+    assert_eq!(get_meeting_from_box_score_html(League::Nba, "401307733", "", None).is_none(), true);
+    let not_found = "<html><body><h1>Page not found</h1></body></html>";
+    assert_eq!(get_meeting_from_box_score_html(League::Nba, "401307733", not_found, None).is_none(), true);
+
+    let matchup = matchup("tor", "bkn", vec![2021], vec![meeting]);
+    assert_eq!(matchup.team_a.wins, 0);
+    assert_eq!(matchup.team_a.home_losses, 1);
+    assert_eq!(matchup.team_b.away_wins, 1);
+    assert_eq!(matchup.team_b.points_per_game, Some(116.0));
+}
+
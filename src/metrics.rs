@@ -0,0 +1,150 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request, Response};
+
+use crate::fixtures::PageKind;
+
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    request_duration: HistogramVec,
+    fetches: IntCounterVec,
+    fetch_duration: HistogramVec,
+    fetch_errors: IntCounterVec,
+    parser_outputs: IntCounterVec,
+    selector_misses: IntCounterVec,
+    page_cache: IntCounterVec
+}
+
+fn counter(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let counter = IntCounterVec::new(Opts::new(name, help), labels).unwrap();
+    registry.register(Box::new(counter.clone())).unwrap();
+    return counter;
+}
+
+fn histogram(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> HistogramVec {
+    // scrapes that fan out to a dozen ESPN pages take seconds, so the buckets run past the default 10s
+    let buckets = vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+    let histogram = HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), labels).unwrap();
+    registry.register(Box::new(histogram.clone())).unwrap();
+    return histogram;
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let registry = Registry::new();
+        return Metrics {
+            requests: counter(&registry, "http_requests_total", "Requests handled, by route and status", &["route", "method", "status"]),
+            request_duration: histogram(&registry, "http_request_duration_seconds", "Time to respond, by route", &["route"]),
+            fetches: counter(&registry, "espn_fetches_total", "Outbound ESPN fetches, by page type and status; status is \"error\" when no response came back", &["page", "status"]),
+            fetch_duration: histogram(&registry, "espn_fetch_duration_seconds", "Time until ESPN's response headers, retries included, by page type", &["page"]),
+            fetch_errors: counter(&registry, "espn_fetch_errors_total", "ESPN fetches that ended in a 5xx, a 429 or no response, by page type", &["page"]),
+            parser_outputs: counter(&registry, "parser_outputs_total", "Parser runs, by parser function and outcome: ok, empty or panicked", &["parser", "outcome"]),
+            selector_misses: counter(&registry, "parser_selector_misses_total", "Required selectors that matched nothing, by parser", &["parser", "selector"]),
+            page_cache: counter(&registry, "page_cache_requests_total", "Page cache outcomes: hit (ESPN answered 304), miss (fresh body stored) or stale (served after ESPN failed)", &["result"]),
+            registry
+        };
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    return METRICS.get_or_init(Metrics::new);
+}
+
+// the Prometheus text exposition format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&metrics().registry.gather(), &mut buffer).unwrap();
+    return String::from_utf8(buffer).unwrap();
+}
+
+fn page_label(page: Option<PageKind>) -> String {
+    return match page {
+        Some(page) => serde_json::to_value(page).unwrap().as_str().unwrap().to_string(),
+        None => "other".to_string()
+    };
+}
+
+pub fn fetched(page: Option<PageKind>, status: Option<u16>, elapsed: Duration) {
+    let metrics = metrics();
+    let page = page_label(page);
+    let status_label = status.map(|status| status.to_string()).unwrap_or("error".to_string());
+    metrics.fetches.with_label_values(&[&page, &status_label]).inc();
+    metrics.fetch_duration.with_label_values(&[&page]).observe(elapsed.as_secs_f64());
+    if status.map(|status| status >= 500 || status == 429).unwrap_or(true) {
+        metrics.fetch_errors.with_label_values(&[&page]).inc();
+    }
+}
+
+// runs a parser and counts whether it produced anything, e.g. `get_odds_for_game_html` returning None for every game
+// when ESPN changes the odds markup. a panic is counted too, then carried on to the caller
+pub fn parsed<T>(parser: &str, parse: impl FnOnce() -> T, produced_output: impl Fn(&T) -> bool) -> T {
+    let _tally = crate::scrape_health::tally_selectors();
+    let output = match panic::catch_unwind(AssertUnwindSafe(parse)) {
+        Ok(output) => output,
+        Err(error) => {
+            metrics().parser_outputs.with_label_values(&[parser, "panicked"]).inc();
+            panic::resume_unwind(error);
+        }
+    };
+    let outcome = match produced_output(&output) {
+        true => "ok",
+        false => "empty"
+    };
+    metrics().parser_outputs.with_label_values(&[parser, outcome]).inc();
+    return output;
+}
+
+pub fn selector_missed(parser: &str, selector: &str) {
+    metrics().selector_misses.with_label_values(&[parser, selector]).inc();
+}
+
+pub fn page_cache(result: &str) {
+    metrics().page_cache.with_label_values(&[result]).inc();
+}
+
+struct Started(Instant);
+
+// counts and times every request; requests no route matched share one label so stray URLs can't grow the series
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        return Info { name: "Request metrics", kind: Kind::Request | Kind::Response };
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| Started(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Started(started) = request.local_cache(|| Started(Instant::now()));
+        let route = request.route().and_then(|route| route.name.as_deref()).unwrap_or("unmatched");
+        let metrics = metrics();
+        metrics.requests.with_label_values(&[route, request.method().as_str(), &response.status().code.to_string()]).inc();
+        metrics.request_duration.with_label_values(&[route]).observe(started.elapsed().as_secs_f64());
+    }
+}
+
+#[test]
+fn render_test() {
+    fetched(Some(PageKind::GamePage), Some(200), Duration::from_millis(120));
+    fetched(Some(PageKind::GamePage), None, Duration::from_secs(20));
+    fetched(None, Some(503), Duration::from_millis(40));
+    parsed("get_odds_for_game_html", || None::<u32>, |odds| odds.is_some());
+    let panicked = panic::catch_unwind(|| parsed("get_teams", || -> Vec<u32> { panic!("no team links") }, |teams| !teams.is_empty()));
+    assert_eq!(panicked.is_err(), true);
+    let text = render();
+    assert_eq!(text.contains("espn_fetches_total{page=\"game_page\",status=\"200\"}"), true);
+    assert_eq!(text.contains("espn_fetches_total{page=\"game_page\",status=\"error\"}"), true);
+    assert_eq!(text.contains("espn_fetch_errors_total{page=\"other\"}"), true);
+    assert_eq!(text.contains("espn_fetch_duration_seconds_bucket{page=\"game_page\",le=\"0.25\"}"), true);
+    assert_eq!(text.contains("parser_outputs_total{outcome=\"empty\",parser=\"get_odds_for_game_html\"}"), true);
+    assert_eq!(text.contains("parser_outputs_total{outcome=\"panicked\",parser=\"get_teams\"}"), true);
+}
@@ -0,0 +1,273 @@
+use scraper::Html;
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+use serde_json::Value;
+
+use crate::{GameOdds, get_script_assignment};
+use crate::scrape_health::tracked_select_optional;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct NumericOdds {
+    pub favourite: Option<String>,
+    pub home: SideOdds,
+    pub away: SideOdds,
+    pub over_under: Option<f64>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct SideOdds {
+    pub spread: Option<f64>,
+    pub moneyline: Option<i32>,
+    pub decimal: Option<f64>,
+    pub fractional: Option<String>,
+    pub implied_probability: Option<f64>,
+    pub fair_probability: Option<f64>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ProviderLine {
+    pub provider: String,
+    pub away_spread: Option<f64>,
+    pub away_spread_price: Option<i32>,
+    pub home_spread: Option<f64>,
+    pub home_spread_price: Option<i32>,
+    pub away_moneyline: Option<i32>,
+    pub home_moneyline: Option<i32>,
+    pub over_under: Option<f64>,
+    pub over_price: Option<i32>,
+    pub under_price: Option<i32>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct MatchupPredictor {
+    pub home_win_percentage: f64,
+    pub away_win_percentage: f64
+}
+
+// ESPN shows "PK" for a pick'em spread
+pub fn parse_spread(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("pk") {
+        return Some(0.0);
+    }
+    return value.trim_start_matches('+').parse::<f64>().ok();
+}
+
+// ESPN shows "EVEN" for a +100 moneyline
+pub fn parse_moneyline(value: &str) -> Option<i32> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("even") {
+        return Some(100);
+    }
+    return match value.trim_start_matches('+').parse::<i32>() {
+        Ok(moneyline) if moneyline.abs() >= 100 => Some(moneyline),
+        _ => None
+    };
+}
+
+pub fn parse_total(value: &str) -> Option<f64> {
+    return value.trim().parse::<f64>().ok();
+}
+
+pub fn moneyline_to_decimal(moneyline: i32) -> f64 {
+    return match moneyline > 0 {
+        true => 1.0 + moneyline as f64 / 100.0,
+        false => 1.0 + 100.0 / moneyline.abs() as f64
+    };
+}
+
+pub fn moneyline_to_fractional(moneyline: i32) -> String {
+    let (numerator, denominator) = match moneyline > 0 {
+        true => (moneyline.abs(), 100),
+        false => (100, moneyline.abs())
+    };
+    let divisor = gcd(numerator, denominator);
+    return format!("{}/{}", numerator / divisor, denominator / divisor);
+}
+
+pub fn implied_probability(moneyline: i32) -> f64 {
+    return 1.0 / moneyline_to_decimal(moneyline);
+}
+
+// removes the bookmaker margin by normalising both implied probabilities so they sum to 1
+pub fn fair_probabilities(home_moneyline: i32, away_moneyline: i32) -> (f64, f64) {
+    let home = implied_probability(home_moneyline);
+    let away = implied_probability(away_moneyline);
+    return (home / (home + away), away / (home + away));
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    return match b {
+        0 => a,
+        _ => gcd(b, a % b)
+    };
+}
+
+fn round(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    return (value * factor).round() / factor;
+}
+
+fn side_odds(spread: &str, moneyline: &str) -> SideOdds {
+    let moneyline = parse_moneyline(moneyline);
+    return SideOdds {
+        spread: parse_spread(spread),
+        moneyline,
+        decimal: moneyline.map(|m| round(moneyline_to_decimal(m), 2)),
+        fractional: moneyline.map(moneyline_to_fractional),
+        implied_probability: moneyline.map(|m| round(implied_probability(m), 4)),
+        fair_probability: None
+    };
+}
+
+pub fn numeric_odds(game_odds: &GameOdds) -> NumericOdds {
+    let mut home = side_odds(&game_odds.home_spread, &game_odds.home_moneyline);
+    let mut away = side_odds(&game_odds.away_spread, &game_odds.away_moneyline);
+    if let (Some(home_moneyline), Some(away_moneyline)) = (home.moneyline, away.moneyline) {
+        let (home_fair, away_fair) = fair_probabilities(home_moneyline, away_moneyline);
+        home.fair_probability = Some(round(home_fair, 4));
+        away.fair_probability = Some(round(away_fair, 4));
+    }
+    let favourite = match (home.moneyline, away.moneyline, home.spread, away.spread) {
+        (Some(h), Some(a), _, _) if h != a => Some(if h < a { &game_odds.home_team } else { &game_odds.away_team }),
+        (_, _, Some(h), Some(a)) if h != a => Some(if h < a { &game_odds.home_team } else { &game_odds.away_team }),
+        _ => None
+    };
+    return NumericOdds {
+        favourite: favourite.map(|team| team.to_string()),
+        home,
+        away,
+        over_under: parse_total(&game_odds.over_under)
+    };
+}
+
+fn price(value: &Value) -> Option<i32> {
+    return value.as_f64().map(|price| price as i32);
+}
+
+pub fn provider_line(line: &Value) -> Option<ProviderLine> {
+    let home_spread = line["spread"].as_f64();
+    return Some(ProviderLine {
+        provider: line["provider"]["name"].as_str()?.to_string(),
+        away_spread: home_spread.map(|spread| -spread),
+        away_spread_price: price(&line["awayTeamOdds"]["spreadOdds"]),
+        home_spread,
+        home_spread_price: price(&line["homeTeamOdds"]["spreadOdds"]),
+        away_moneyline: price(&line["awayTeamOdds"]["moneyLine"]),
+        home_moneyline: price(&line["homeTeamOdds"]["moneyLine"]),
+        over_under: line["overUnder"].as_f64(),
+        over_price: price(&line["overOdds"]),
+        under_price: price(&line["underOdds"])
+    });
+}
+
+// ESPN's average across sportsbooks, listed among them under the provider name "consensus"
+pub fn consensus_line(providers: &[ProviderLine]) -> Option<ProviderLine> {
+    return providers.iter().find(|line| line.provider == "consensus").cloned();
+}
+
+// the game page carries every sportsbook's line in a script assignment rather than in markup
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_provider_lines_from_html(html: &str) -> Vec<ProviderLine> {
+    return crate::metrics::parsed("get_provider_lines_from_html", || parse_provider_lines_from_html(html), |lines| !lines.is_empty());
+}
+
+fn parse_provider_lines_from_html(html: &str) -> Vec<ProviderLine> {
+    let assignment = match get_script_assignment(html, "espn.gamepackage.pickcenter") {
+        Some(assignment) => assignment,
+        None => return vec![]
+    };
+    return match serde_json::from_str::<Vec<Value>>(assignment) {
+        Ok(lines) => lines.iter().filter_map(provider_line).collect(),
+        Err(_) => vec![]
+    };
+}
+
+pub fn get_matchup_predictor_from_html(fragment: &Html) -> Option<MatchupPredictor> {
+    let percentage = |selector: &str| -> Option<f64> {
+        let span = tracked_select_optional(fragment.root_element(), selector).into_iter().next()?;
+        return span.text().collect::<String>().trim().trim_end_matches('%').parse::<f64>().ok();
+    };
+    return Some(MatchupPredictor {
+        home_win_percentage: percentage("matchup_predictor.home")?,
+        away_win_percentage: percentage("matchup_predictor.away")?
+    });
+}
+
+// odds with exactly the given lines, for tests about what happens to them rather than how a page is parsed
+#[cfg(test)]
+pub fn game_odds_fixture(away_team: &str, home_team: &str, away_spread: &str, home_spread: &str, away_moneyline: &str, home_moneyline: &str, over_under: &str) -> GameOdds {
+    let mut game_odds = GameOdds {
+        home_team: home_team.to_string(),
+        away_team: away_team.to_string(),
+        home_spread: home_spread.to_string(),
+        away_spread: away_spread.to_string(),
+        home_moneyline: home_moneyline.to_string(),
+        away_moneyline: away_moneyline.to_string(),
+        over_under: over_under.to_string(),
+        numeric: None,
+        providers: vec![],
+        consensus: None,
+        matchup_predictor: None
+    };
+    game_odds.numeric = Some(numeric_odds(&game_odds));
+    return game_odds;
+}
+
+#[test]
+fn parse_odds_strings_test() {
+    assert_eq!(parse_spread("-4.0"), Some(-4.0));
+    assert_eq!(parse_spread("+4"), Some(4.0));
+    assert_eq!(parse_spread("PK"), Some(0.0));
+    assert_eq!(parse_spread(""), None);
+    // EVEN is a moneyline, not a spread
+    assert_eq!(parse_spread("EVEN"), None);
+    assert_eq!(parse_moneyline(" +155 "), Some(155));
+    assert_eq!(parse_moneyline("-180"), Some(-180));
+    assert_eq!(parse_moneyline("EVEN"), Some(100));
+    assert_eq!(parse_moneyline("--"), None);
+    assert_eq!(parse_total("223.0"), Some(223.0));
+}
+
+#[test]
+fn moneyline_conversions_test() {
+    assert_eq!(moneyline_to_decimal(155), 2.55);
+    assert_eq!(round(moneyline_to_decimal(-180), 2), 1.56);
+    assert_eq!(moneyline_to_fractional(155), "31/20");
+    assert_eq!(moneyline_to_fractional(-180), "5/9");
+    assert_eq!(moneyline_to_fractional(100), "1/1");
+    let (home, away) = fair_probabilities(155, -180);
+    assert_eq!(round(home, 4), 0.3789);
+    assert_eq!(round(away, 4), 0.6211);
+    assert!(implied_probability(155) + implied_probability(-180) > 1.0);
+}
+
+#[test]
+fn get_provider_lines_from_html_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let lines = get_provider_lines_from_html(&contents);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].provider, "Caesars Sportsbook (New Jersey)");
+    assert_eq!(lines[0].away_spread, Some(-4.0));
+    assert_eq!(lines[0].home_spread, Some(4.0));
+    assert_eq!(lines[0].away_spread_price, Some(-110));
+    assert_eq!(lines[0].away_moneyline, Some(-180));
+    assert_eq!(lines[0].home_moneyline, Some(155));
+    assert_eq!(lines[0].over_under, Some(223.0));
+    assert_eq!(lines[1].provider, "consensus");
+    assert_eq!(lines[1].over_under, Some(223.5));
+    assert_eq!(lines[2].home_spread_price, Some(-105));
+
+    let not_found = std::fs::read_to_string("./test-data/game-page-for-odds-not-found.html").unwrap();
+    assert_eq!(get_provider_lines_from_html(&not_found).len(), 0);
+    assert_eq!(consensus_line(&lines).unwrap().home_spread, Some(4.5));
+    assert_eq!(consensus_line(&lines[..1]), None);
+}
+
+#[test]
+fn get_matchup_predictor_from_html_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let predictor = get_matchup_predictor_from_html(&Html::parse_document(&contents)).unwrap();
+    assert_eq!(predictor.home_win_percentage, 50.2);
+    assert_eq!(predictor.away_win_percentage, 49.8);
+}
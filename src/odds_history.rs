@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::{GameOdds, get_odds_for_game, get_teams, find_upcoming_game_id_from_html};
+use crate::league::League;
+use crate::upstream;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OddsHistoryConfig {
+    #[serde(default = "default_odds_history_path")]
+    pub odds_history_path: String,
+    #[serde(default = "default_odds_snapshot_interval")]
+    pub odds_snapshot_interval: u64
+}
+
+fn default_odds_history_path() -> String {
+    return "odds-history.json".to_string();
+}
+
+fn default_odds_snapshot_interval() -> u64 {
+    return 15 * 60;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct OddsSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub odds: GameOdds
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Market {
+    Spread,
+    Moneyline,
+    Total
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct MarketLine {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    away: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    home: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LineChange {
+    taken_at: DateTime<Utc>,
+    market: Market,
+    from: MarketLine,
+    to: MarketLine
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OddsHistory {
+    game_id: String,
+    open: OddsSnapshot,
+    current: OddsSnapshot,
+    changes: Vec<LineChange>
+}
+
+#[derive(Clone)]
+pub struct OddsHistoryStore {
+    path: PathBuf,
+    series: Arc<Mutex<HashMap<String, Vec<OddsSnapshot>>>>,
+    // bumped with every change to `series`, so a write that loses the race to a newer copy is skipped
+    generation: Arc<AtomicU64>,
+    written: Arc<tokio::sync::Mutex<u64>>
+}
+
+// series are keyed "<league>/<game_id>", e.g. "nba/401360432"
+fn key(league: League, game_id: &str) -> String {
+    return format!("{}/{}", league.path(), game_id);
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}", suffix));
+    return path.with_file_name(file_name);
+}
+
+// e.g. odds-history.json.corrupt-1641816000
+fn corrupt_path(path: &Path) -> PathBuf {
+    return with_suffix(path, &format!("corrupt-{}", Utc::now().timestamp()));
+}
+
+fn move_aside(path: &Path, error: &str) -> Result<HashMap<String, Vec<OddsSnapshot>>, String> {
+    let aside = corrupt_path(path);
+    fs::rename(path, &aside).map_err(|rename_error| format!("could not move unreadable {} aside: {}", path.display(), rename_error))?;
+    tracing::warn!(path = %path.display(), moved_to = %aside.display(), error, "odds_history_unreadable");
+    return Ok(HashMap::new());
+}
+
+impl OddsHistoryStore {
+    // a file that doesn't parse, e.g. one cut short by a crash or not UTF-8, is moved aside rather than overwritten by
+    // the next snapshot. only a missing file starts an empty history; Err when the file can't be read or moved aside
+    pub fn open(path: PathBuf) -> Result<OddsHistoryStore, String> {
+        let series: HashMap<String, Vec<OddsSnapshot>> = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str(&contents) {
+                Ok(series) => series,
+                Err(error) => move_aside(&path, &error.to_string())?
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) if error.kind() == io::ErrorKind::InvalidData => move_aside(&path, &error.to_string())?,
+            Err(error) => return Err(format!("could not read {}: {}", path.display(), error))
+        };
+        return Ok(OddsHistoryStore { path, series: Arc::new(Mutex::new(series)), generation: Arc::new(AtomicU64::new(0)), written: Arc::new(tokio::sync::Mutex::new(0)) });
+    }
+
+    // written next to the file and renamed over it, so a crash mid-write leaves the previous copy intact
+    async fn write(&self, contents: String) -> std::io::Result<()> {
+        let temporary = with_suffix(&self.path, "tmp");
+        tokio::fs::write(&temporary, contents).await?;
+        return tokio::fs::rename(&temporary, &self.path).await;
+    }
+
+    // only keeps a snapshot when the spread, moneyline or total moved since the last one
+    pub async fn record(&self, league: League, game_id: &str, odds: GameOdds, taken_at: DateTime<Utc>) -> bool {
+        let (generation, series) = {
+            let mut series = self.series.lock().unwrap();
+            let snapshots = series.entry(key(league, game_id)).or_default();
+            if let Some(last) = snapshots.last() {
+                if market_lines(&last.odds) == market_lines(&odds) {
+                    return false;
+                }
+            }
+            snapshots.push(OddsSnapshot { taken_at, odds });
+            (self.generation.fetch_add(1, Ordering::SeqCst) + 1, series.clone())
+        };
+        // written from the copy, so readers of the history aren't held up by the disk
+        let mut written = self.written.lock().await;
+        if generation > *written {
+            let contents = serde_json::to_string(&series).unwrap();
+            match self.write(contents).await {
+                Ok(()) => *written = generation,
+                Err(error) => tracing::error!(path = %self.path.display(), error = %error, "odds_history_write_failed")
+            }
+        }
+        return true;
+    }
+
+    pub fn snapshots(&self, league: League, game_id: &str) -> Vec<OddsSnapshot> {
+        return self.series.lock().unwrap().get(&key(league, game_id)).cloned().unwrap_or_default();
+    }
+
+    pub fn history(&self, league: League, game_id: &str) -> Option<OddsHistory> {
+        return odds_history(game_id, &self.snapshots(league, game_id));
+    }
+}
+
+fn market_lines(odds: &GameOdds) -> Vec<(Market, MarketLine)> {
+    return vec![
+        (Market::Spread, MarketLine { away: Some(odds.away_spread.clone()), home: Some(odds.home_spread.clone()), total: None }),
+        (Market::Moneyline, MarketLine { away: Some(odds.away_moneyline.clone()), home: Some(odds.home_moneyline.clone()), total: None }),
+        (Market::Total, MarketLine { away: None, home: None, total: Some(odds.over_under.clone()) })
+    ];
+}
+
+pub fn odds_history(game_id: &str, snapshots: &[OddsSnapshot]) -> Option<OddsHistory> {
+    let open = snapshots.first()?;
+    let current = snapshots.last()?;
+    let mut changes = Vec::new();
+    for pair in snapshots.windows(2) {
+        let previous = market_lines(&pair[0].odds);
+        let next = market_lines(&pair[1].odds);
+        for ((market, from), (_, to)) in previous.into_iter().zip(next) {
+            if from != to {
+                changes.push(LineChange { taken_at: pair[1].taken_at, market, from, to });
+            }
+        }
+    }
+    return Some(OddsHistory {
+        game_id: game_id.to_string(),
+        open: open.clone(),
+        current: current.clone(),
+        changes
+    });
+}
+
+async fn upcoming_game_ids(league: League) -> Option<Vec<String>> {
+    let teams = get_teams(upstream::get(league.url("teams")).await.ok()?.text().await.ok()?, league);
+    let mut game_ids = HashSet::new();
+    for team in teams {
+        let team_page_html = match upstream::get(league.url(&format!("team/_/name/{}", team.id))).await {
+            Ok(response) => response.text().await.unwrap_or_default(),
+            Err(_) => continue
+        };
+        if let Some(game_id) = find_upcoming_game_id_from_html(&team_page_html) {
+            game_ids.insert(game_id);
+        }
+    }
+    return Some(game_ids.into_iter().collect());
+}
+
+pub async fn snapshot_upcoming_odds(store: &OddsHistoryStore, leagues: &[League]) {
+    for league in leagues {
+        for game_id in upcoming_game_ids(*league).await.unwrap_or_default() {
+            if let Some(odds) = get_odds_for_game(*league, game_id.to_string()).await {
+                store.record(*league, &game_id, odds, Utc::now()).await;
+            }
+        }
+    }
+}
+
+pub fn spawn_snapshotter(store: OddsHistoryStore, interval: u64, leagues: Vec<League>) {
+    tokio::spawn(async move {
+        // tokio's interval panics on a zero period
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        loop {
+            ticker.tick().await;
+            snapshot_upcoming_odds(&store, &leagues).await;
+        }
+    });
+}
+
+#[cfg(test)]
+fn odds_fixture(away_spread: &str, home_moneyline: &str, over_under: &str) -> GameOdds {
+    return crate::odds::game_odds_fixture("phx", "tor", away_spread, "+4", "-180", home_moneyline, over_under);
+}
+
+#[rocket::async_test]
+async fn odds_history_records_only_changes_test() {
+    let path = std::env::temp_dir().join("qr-odds-history-records-only-changes-test.json");
+    let _ = fs::remove_file(&path);
+    let store = OddsHistoryStore::open(path.clone()).unwrap();
+    let opened: DateTime<Utc> = "2022-01-10T12:00:00Z".parse().unwrap();
+    let moved: DateTime<Utc> = "2022-01-11T12:00:00Z".parse().unwrap();
+    assert_eq!(store.record(League::Nba, "401360432", odds_fixture("-4.0", "+155", "223.0"), opened).await, true);
+    assert_eq!(store.record(League::Nba, "401360432", odds_fixture("-4.0", "+155", "223.0"), moved).await, false);
+    assert_eq!(store.record(League::Nba, "401360432", odds_fixture("-5.5", "+155", "224.5"), moved).await, true);
+
+    let history = store.history(League::Nba, "401360432").unwrap();
+    assert_eq!(history.open.taken_at, opened);
+    assert_eq!(history.current.taken_at, moved);
+    assert_eq!(history.changes.len(), 2);
+    assert_eq!(history.changes[0].market, Market::Spread);
+    assert_eq!(history.changes[0].from.away, Some("-4.0".to_string()));
+    assert_eq!(history.changes[0].to.away, Some("-5.5".to_string()));
+    assert_eq!(history.changes[1].market, Market::Total);
+    assert_eq!(history.changes[1].to.total, Some("224.5".to_string()));
+
+    let reopened = OddsHistoryStore::open(path.clone()).unwrap();
+    assert_eq!(reopened.snapshots(League::Nba, "401360432").len(), 2);
+    assert_eq!(reopened.history(League::Nba, "401360433").is_none(), true);
+    let _ = fs::remove_file(&path);
+}
+
+#[rocket::async_test]
+async fn odds_history_moves_unreadable_file_aside_test() {
+    let dir = std::env::temp_dir().join(format!("qr-odds-history-unreadable-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("odds-history.json");
+    fs::write(&path, "{\"nba/401360432\": [{\"taken_at\"").unwrap();
+    let store = OddsHistoryStore::open(path.clone()).unwrap();
+    assert_eq!(store.snapshots(League::Nba, "401360432").len(), 0);
+    let moved = fs::read_dir(&dir).unwrap().map(|file| file.unwrap().file_name().to_string_lossy().to_string()).collect::<Vec<String>>();
+    assert_eq!(moved.len(), 1);
+    assert_eq!(moved[0].starts_with("odds-history.json.corrupt-"), true);
+
+    store.record(League::Nba, "401360432", odds_fixture("-4.0", "+155", "223.0"), Utc::now()).await;
+    assert_eq!(OddsHistoryStore::open(path.clone()).unwrap().snapshots(League::Nba, "401360432").len(), 1);
+    // the temporary file was renamed over the history
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+
+    // not UTF-8 is as unreadable as not JSON
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+    assert_eq!(OddsHistoryStore::open(path.clone()).unwrap().snapshots(League::Nba, "401360432").len(), 0);
+    assert_eq!(fs::read_dir(&dir).unwrap().next().unwrap().unwrap().file_name().to_string_lossy().starts_with("odds-history.json.corrupt-"), true);
+    fs::remove_dir_all(&dir).unwrap();
+
+    // a directory where the file should be can't be read, and isn't replaced
+    fs::create_dir_all(&path).unwrap();
+    assert_eq!(OddsHistoryStore::open(path.clone()).is_err(), true);
+    assert_eq!(path.is_dir(), true);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,128 @@
+use serde_json::{Map, Value};
+use utoipa::{Modify, OpenApi};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+
+use crate::v1;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "espn-nba-scraper-api", description = "Box scores, lineups, injuries and odds scraped from ESPN"),
+    paths(
+        v1::teams, v1::injuries, v1::box_score, v1::previous_results, v1::probable_lineup, v1::ats_report, v1::matchup,
+        v1::game_preview, v1::game_odds, v1::game_odds_history, crate::scraper_health, crate::prometheus_metrics, crate::box_score_widget,
+        crate::injury_card_widget, crate::lineup_widget, crate::odds_strip_widget, crate::api_usage,
+        crate::cache_entries, crate::purge_cache, crate::health::live, crate::health::ready, crate::health::version
+    ),
+    security(("api_key" = [])),
+    modifiers(&ApiKeyScheme)
+)]
+pub struct ApiDoc;
+
+struct ApiKeyScheme;
+
+impl Modify for ApiKeyScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))));
+        // widgets also take the key in the query string, for iframes
+        components.add_security_scheme("api_key_query", SecurityScheme::ApiKey(ApiKey::Query(ApiKeyValue::new("api_key"))));
+    }
+}
+
+pub fn document() -> Value {
+    return serde_json::to_value(ApiDoc::openapi()).unwrap();
+}
+
+pub fn schema_names() -> Vec<String> {
+    let document = document();
+    let mut names = document["components"]["schemas"].as_object().unwrap().keys().cloned().collect::<Vec<String>>();
+    names.sort();
+    return names;
+}
+
+// points component refs at `$defs` and collects the names they reference
+fn rewrite_refs(value: &mut Value, referenced: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                if let Some(name) = reference.strip_prefix("#/components/schemas/") {
+                    referenced.push(name.to_string());
+                    *reference = format!("#/$defs/{}", name);
+                }
+            }
+            map.values_mut().for_each(|value| rewrite_refs(value, referenced));
+        }
+        Value::Array(values) => values.iter_mut().for_each(|value| rewrite_refs(value, referenced)),
+        _ => ()
+    }
+}
+
+// utoipa emits OpenAPI 3.1, whose component schemas are already JSON Schema 2020-12; they only need their refs made self-contained
+pub fn json_schema(name: &str) -> Option<Value> {
+    let document = document();
+    let components = document["components"]["schemas"].as_object().unwrap();
+    components.get(name)?;
+    let mut defs = Map::new();
+    let mut pending = vec![name.to_string()];
+    while let Some(next) = pending.pop() {
+        if defs.contains_key(&next) {
+            continue;
+        }
+        let mut schema = components[&next].clone();
+        rewrite_refs(&mut schema, &mut pending);
+        defs.insert(next, schema);
+    }
+    let mut schema = defs.remove(name).unwrap();
+    schema["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    schema["title"] = json!(name);
+    if !defs.is_empty() {
+        schema["$defs"] = Value::Object(defs);
+    }
+    return Some(schema);
+}
+
+pub const REDOC_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>espn-nba-scraper-api</title>
+</head>
+<body>
+<redoc spec-url="/openapi.json"></redoc>
+<script src="https://cdn.redoc.ly/redoc/v2.1.5/bundles/redoc.standalone.js"></script>
+</body>
+</html>
+"#;
+
+#[test]
+fn every_route_is_documented_test() {
+    let document = document();
+    let paths = document["paths"].as_object().unwrap();
+    let docs_routes = ["/openapi.json", "/docs", "/openapi/schemas", "/openapi/schemas/<name>"];
+    // the unversioned aliases are left out so clients generate against /v1
+    let legacy_routes = crate::legacy_routes().iter().map(|route| route.uri.to_string()).collect::<Vec<String>>();
+    for route in crate::rocket().routes() {
+        let path = route.uri.path().to_string();
+        if docs_routes.contains(&path.as_str()) || legacy_routes.contains(&route.uri.to_string()) {
+            continue;
+        }
+        // rocket's `<_league>` is `{league}` in the document
+        let documented = path.replace("<_", "{").replace('<', "{").replace('>', "}");
+        let method = route.method.as_str().to_lowercase();
+        assert_eq!(paths.get(&documented).map(|operations| operations.get(&method).is_some()), Some(true), "{} {} is not in the OpenAPI document", method, documented);
+    }
+}
+
+#[test]
+fn json_schema_test() {
+    let schema = json_schema("TeamBox").unwrap();
+    assert_eq!(schema["title"], "TeamBox");
+    assert_eq!(schema["properties"]["player_records"]["items"]["$ref"], "#/$defs/Player");
+    assert_eq!(schema["$defs"]["Player"]["properties"]["player"]["$ref"], "#/$defs/PlayerBoxScore");
+    assert_eq!(serde_json::to_string(&schema).unwrap().contains("#/components/"), false);
+    assert_eq!(schema_names().contains(&"UpcomingLineups".to_string()), true);
+    assert_eq!(json_schema("NotAType"), None);
+    // the flat odds fields say which line they hold
+    assert_eq!(json_schema("GameOdds").unwrap()["description"].as_str().unwrap().contains("consensus"), true);
+}
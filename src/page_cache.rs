@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PageCacheConfig {
+    // off unless asked for: nothing caps the directory's size or evicts old pages, so it belongs where the disk is
+    // watched or purged through /admin/cache
+    #[serde(default = "default_page_cache_enabled")]
+    pub page_cache_enabled: bool,
+    #[serde(default = "default_page_cache_path")]
+    pub page_cache_path: String
+}
+
+fn default_page_cache_enabled() -> bool {
+    return false;
+}
+
+fn default_page_cache_path() -> String {
+    return "page-cache".to_string();
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CacheEntry {
+    pub url: String,
+    // the raw body, next to this entry's `.meta.json` in the cache directory
+    pub file: String,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    // when the body was last downloaded
+    pub fetched_at: DateTime<Utc>,
+    // when ESPN last confirmed it, with a 200 or a 304
+    pub validated_at: DateTime<Utc>,
+    pub bytes: usize
+}
+
+// one body file per URL with a readable name, e.g. `www-espn-com-nba-team-name-tor.html`, so a cached page
+// can be copied straight into test-data/ as a fixture. every method is blocking file I/O, so async callers go
+// through `spawn_blocking`
+pub struct PageCache {
+    dir: PathBuf
+}
+
+pub fn slug(url: &str) -> String {
+    let without_scheme = url.split("://").last().unwrap();
+    let slug = without_scheme.split(|c: char| !c.is_ascii_alphanumeric()).filter(|part| !part.is_empty()).collect::<Vec<&str>>().join("-");
+    return slug.chars().take(150).collect();
+}
+
+fn extension(content_type: Option<&str>) -> &'static str {
+    return match content_type {
+        Some(content_type) if content_type.contains("json") => "json",
+        _ => "html"
+    };
+}
+
+impl PageCache {
+    pub fn open(dir: PathBuf) -> Result<PageCache, String> {
+        fs::create_dir_all(&dir).map_err(|error| format!("could not create {}: {}", dir.display(), error))?;
+        return Ok(PageCache { dir });
+    }
+
+    fn meta_path(&self, url: &str) -> PathBuf {
+        return self.dir.join(format!("{}.meta.json", slug(url)));
+    }
+
+    fn entry(&self, url: &str) -> Option<CacheEntry> {
+        let entry = serde_json::from_str::<CacheEntry>(&fs::read_to_string(self.meta_path(url)).ok()?).ok()?;
+        // two URLs can share a slug; the entry records which one it holds
+        return match entry.url == url {
+            true => Some(entry),
+            false => None
+        };
+    }
+
+    pub fn get(&self, url: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let entry = self.entry(url)?;
+        let body = fs::read(self.dir.join(&entry.file)).ok()?;
+        return Some((entry, body));
+    }
+
+    pub fn put(&self, url: &str, content_type: Option<String>, etag: Option<String>, last_modified: Option<String>, body: &[u8]) -> CacheEntry {
+        let now = Utc::now();
+        let entry = CacheEntry {
+            url: url.to_string(),
+            file: format!("{}.{}", slug(url), extension(content_type.as_deref())),
+            content_type,
+            etag,
+            last_modified,
+            fetched_at: now,
+            validated_at: now,
+            bytes: body.len()
+        };
+        let _ = fs::write(self.dir.join(&entry.file), body);
+        let _ = fs::write(self.meta_path(url), serde_json::to_string_pretty(&entry).unwrap());
+        return entry;
+    }
+
+    pub fn revalidated(&self, entry: &CacheEntry) {
+        let entry = CacheEntry { validated_at: Utc::now(), ..entry.clone() };
+        let _ = fs::write(self.meta_path(&entry.url), serde_json::to_string_pretty(&entry).unwrap());
+    }
+
+    // empty when the directory has gone missing, e.g. deleted by hand while the server runs
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        let files = match fs::read_dir(&self.dir) {
+            Ok(files) => files,
+            Err(_) => return vec![]
+        };
+        let mut entries = files
+            .filter_map(|file| file.ok())
+            .filter(|file| file.file_name().to_string_lossy().ends_with(".meta.json"))
+            .filter_map(|file| serde_json::from_str::<CacheEntry>(&fs::read_to_string(file.path()).ok()?).ok())
+            .collect::<Vec<CacheEntry>>();
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        return entries;
+    }
+
+    // stops at the first entry, so readiness probes don't list the whole directory
+    pub fn is_warm(&self) -> bool {
+        return fs::read_dir(&self.dir).map(|mut files| files.any(|file| file.map(|file| file.file_name().to_string_lossy().ends_with(".meta.json")).unwrap_or(false))).unwrap_or(false);
+    }
+
+    // purges entries whose URL starts with `prefix`, or everything without one
+    pub fn purge(&self, prefix: Option<&str>) -> usize {
+        let mut purged = 0;
+        for entry in self.entries() {
+            if prefix.map(|prefix| entry.url.starts_with(prefix)).unwrap_or(true) {
+                let _ = fs::remove_file(self.dir.join(&entry.file));
+                let _ = fs::remove_file(self.meta_path(&entry.url));
+                purged += 1;
+            }
+        }
+        return purged;
+    }
+}
+
+fn configured() -> &'static OnceLock<PageCache> {
+    static CACHE: OnceLock<PageCache> = OnceLock::new();
+    return &CACHE;
+}
+
+pub fn configure(cache: PageCache) {
+    let _ = configured().set(cache);
+}
+
+// None until configured, which leaves the fetch layer uncached
+pub fn cache() -> Option<&'static PageCache> {
+    return configured().get();
+}
+
+#[test]
+fn page_cache_test() {
+    let dir = std::env::temp_dir().join(format!("qr-page-cache-test-{}", std::process::id()));
+    let cache = PageCache::open(dir.clone()).unwrap();
+    let url = "https://www.espn.com/nba/team/_/name/tor";
+    assert_eq!(cache.get(url), None);
+    assert_eq!(cache.is_warm(), false);
+
+    let entry = cache.put(url, Some("text/html; charset=utf-8".to_string()), Some("\"abc\"".to_string()), None, b"<html></html>");
+    assert_eq!(entry.file, "www-espn-com-nba-team-name-tor.html");
+    assert_eq!(cache.get(url), Some((entry.clone(), b"<html></html>".to_vec())));
+    // a URL with the same slug doesn't read another page's body
+    assert_eq!(cache.get("https://www.espn.com/nba/team/_/name/tor/"), None);
+
+    cache.put("https://site.api.espn.com/apis/site/v2/sports/basketball/nba/teams", Some("application/json".to_string()), None, None, b"{}");
+    assert_eq!(cache.entries().len(), 2);
+    assert_eq!(cache.is_warm(), true);
+    assert_eq!(cache.purge(Some("https://www.espn.com/")), 1);
+    assert_eq!(cache.entries().iter().map(|entry| entry.file.as_str()).collect::<Vec<&str>>(), vec!["site-api-espn-com-apis-site-v2-sports-basketball-nba-teams.json"]);
+    assert_eq!(cache.purge(None), 1);
+    fs::remove_dir_all(dir).unwrap();
+    assert_eq!(cache.entries(), vec![]);
+    assert_eq!(cache.purge(None), 0);
+}
+
+#[test]
+fn page_cache_open_error_test() {
+    let file = std::env::temp_dir().join(format!("qr-page-cache-open-error-test-{}", std::process::id()));
+    fs::write(&file, "").unwrap();
+    assert_eq!(PageCache::open(file.join("page-cache")).is_err(), true);
+    fs::remove_file(file).unwrap();
+}
@@ -0,0 +1,162 @@
+use scraper::{Html, ElementRef};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::{GameOdds, ProbableLineup, extract_team_code_from_a_tag, get_script_assignment};
+use crate::scrape_health::{tracked_select, tracked_select_optional};
+use crate::selectors;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PreviewTeam {
+    pub team_code: String,
+    pub name: String,
+    pub record: String,
+    pub split_record: String
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GameInfo {
+    pub game_id: String,
+    pub away_team: PreviewTeam,
+    pub home_team: PreviewTeam,
+    pub tip_off: Option<String>,
+    pub venue: Option<String>,
+    pub location: Option<String>,
+    pub tv: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SeriesGame {
+    pub game_id: String,
+    pub date: Option<String>,
+    pub away_team: String,
+    pub home_team: String,
+    pub away_score: Option<u32>,
+    pub home_score: Option<u32>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GamePreview {
+    pub game: GameInfo,
+    pub away: ProbableLineup,
+    pub home: ProbableLineup,
+    pub head_to_head: Vec<SeriesGame>,
+    pub odds: Option<GameOdds>
+}
+
+fn optional_text(parent_element: ElementRef, selector: &str) -> Option<String> {
+    let element = selectors::select_first(parent_element, selector)?;
+    let text = element.text().collect::<String>().trim().to_string();
+    return match text.is_empty() {
+        true => None,
+        false => Some(text)
+    };
+}
+
+fn preview_team(team_elem: ElementRef) -> Option<PreviewTeam> {
+    let record_elem = selectors::select_first(team_elem, "game_info.record")?;
+    let record = record_elem.text().next().unwrap_or("").trim().to_string();
+    let split_record = optional_text(record_elem, "game_info.split_record").unwrap_or_default();
+    return Some(PreviewTeam {
+        team_code: extract_team_code_from_a_tag("game_info.team_link", team_elem),
+        name: optional_text(team_elem, "game_info.name")?,
+        record,
+        split_record: split_record.trim_start_matches(',').trim().to_string()
+    });
+}
+
+pub fn get_venue_from_html(html: &str) -> Option<String> {
+    return optional_text(Html::parse_document(html).root_element(), "game_info.venue");
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_game_info_from_html(game_id: &str, html: &str) -> Option<GameInfo> {
+    return crate::metrics::parsed("get_game_info_from_html", || parse_game_info_from_html(game_id, html), |game| game.is_some());
+}
+
+fn parse_game_info_from_html(game_id: &str, html: &str) -> Option<GameInfo> {
+    let fragment = Html::parse_document(html);
+    let root = fragment.root_element();
+    let away_elem = tracked_select(root, "game_info.away").into_iter().next()?;
+    let home_elem = tracked_select(root, "game_info.home").into_iter().next()?;
+    let tip_off = selectors::select_first(root, "game_info.tip_off")
+        .and_then(|span| span.value().attr("data-date"))
+        .map(|date| date.to_string());
+    let tv = get_script_assignment(html, "espn.gamepackage.network")
+        .map(|network| network.trim_matches('"').to_string())
+        .filter(|network| !network.is_empty());
+    return Some(GameInfo {
+        game_id: game_id.to_string(),
+        away_team: preview_team(away_elem)?,
+        home_team: preview_team(home_elem)?,
+        tip_off,
+        venue: get_venue_from_html(html),
+        location: optional_text(root, "game_info.location"),
+        tv
+    });
+}
+
+fn series_score(item: ElementRef) -> Option<u32> {
+    return optional_text(item, "season_series.score")?.parse::<u32>().ok();
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(selectors_matched = tracing::field::Empty, selectors_missed = tracing::field::Empty))]
+pub fn get_season_series_from_html(html: &str) -> Vec<SeriesGame> {
+    return crate::metrics::parsed("get_season_series_from_html", || parse_season_series_from_html(html), |series| !series.is_empty());
+}
+
+fn parse_season_series_from_html(html: &str) -> Vec<SeriesGame> {
+    let fragment = Html::parse_document(html);
+    let abbrev_selector = "season_series.abbrev";
+    let mut series = Vec::new();
+    for a in tracked_select_optional(fragment.root_element(), "season_series.games") {
+        let away = selectors::select_first(a, "season_series.away");
+        let home = selectors::select_first(a, "season_series.home");
+        let (away, home) = match (away, home) {
+            (Some(away), Some(home)) => (away, home),
+            _ => continue
+        };
+        series.push(SeriesGame {
+            game_id: a.value().attr("data-gameid").unwrap_or("").to_string(),
+            date: selectors::select_first(a, "season_series.date")
+                .and_then(|div| div.value().attr("data-date"))
+                .map(|date| date.to_string()),
+            away_team: optional_text(away, abbrev_selector).unwrap_or_default().to_lowercase(),
+            home_team: optional_text(home, abbrev_selector).unwrap_or_default().to_lowercase(),
+            away_score: series_score(away),
+            home_score: series_score(home)
+        });
+    }
+    // ESPN lists the series latest game first
+    series.reverse();
+    return series;
+}
+
+#[test]
+fn get_game_info_from_html_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let game = get_game_info_from_html("401360432", &contents).unwrap();
+    assert_eq!(game.away_team.team_code, "phx");
+    assert_eq!(game.away_team.name, "Suns");
+    assert_eq!(game.away_team.record, "30-9");
+    assert_eq!(game.away_team.split_record, "13-4 Away");
+    assert_eq!(game.home_team.team_code, "tor");
+    assert_eq!(game.home_team.split_record, "12-10 Home");
+    assert_eq!(game.tip_off, Some("2022-01-12T00:30Z".to_string()));
+    assert_eq!(game.venue, Some("Scotiabank Arena".to_string()));
+    assert_eq!(game.location, Some("Toronto, ON".to_string()));
+    assert_eq!(game.tv, None);
+}
+
+#[test]
+fn get_season_series_from_html_test() {
+    let contents = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let series = get_season_series_from_html(&contents);
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].game_id, "401360432");
+    assert_eq!(series[0].away_team, "phx");
+    assert_eq!(series[0].home_team, "tor");
+    assert_eq!(series[0].away_score, None);
+    assert_eq!(series[1].game_id, "401360822");
+    assert_eq!(series[1].date, Some("2022-03-12T02:00:00Z".to_string()));
+}
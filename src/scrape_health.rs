@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, OnceLock};
+
+use scraper::ElementRef;
+use tracing::Span;
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+use crate::league::League;
+use crate::upstream;
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SelectorCount {
+    pub parser: String,
+    pub selector: String,
+    pub count: usize,
+    pub optional: bool
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Pass,
+    Degraded,
+    Broken
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ParserHealth {
+    pub parser: String,
+    pub url: String,
+    pub status: HealthStatus,
+    pub failing_selectors: Vec<String>,
+    pub selectors: Vec<SelectorCount>,
+    pub error: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScraperHealth {
+    pub status: HealthStatus,
+    pub parsers: Vec<ParserHealth>
+}
+
+thread_local! {
+    // only set while `check_parser` runs a parser, so normal requests don't accumulate counts
+    static COLLECTOR: RefCell<Option<Vec<SelectorCount>>> = const { RefCell::new(None) };
+    // matched and missed selectors for each parser run in progress, innermost last
+    static TALLIES: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+// tallies the selectors a parser tries and records them as `selectors_matched` and `selectors_missed` on its span once
+// the guard drops, whether the parser returned or panicked
+pub struct SelectorTally;
+
+pub fn tally_selectors() -> SelectorTally {
+    TALLIES.with(|tallies| tallies.borrow_mut().push((0, 0)));
+    return SelectorTally;
+}
+
+impl Drop for SelectorTally {
+    fn drop(&mut self) {
+        if let Some((matched, missed)) = TALLIES.with(|tallies| tallies.borrow_mut().pop()) {
+            let span = Span::current();
+            span.record("selectors_matched", matched);
+            span.record("selectors_missed", missed);
+        }
+    }
+}
+
+fn last_counts() -> &'static Mutex<HashMap<String, usize>> {
+    static LAST_COUNTS: OnceLock<Mutex<HashMap<String, usize>>> = OnceLock::new();
+    return LAST_COUNTS.get_or_init(|| Mutex::new(HashMap::new()));
+}
+
+fn record(parser: &str, selector: &str, count: usize, optional: bool) {
+    let key = format!("{} {}", parser, selector);
+    let previous = last_counts().lock().unwrap().insert(key, count);
+    if count == 0 && !optional {
+        crate::metrics::selector_missed(parser, selector);
+    }
+    if count == 0 && !optional && previous.is_some_and(|previous| previous > 0) {
+        tracing::warn!(parser, selector, previous_count = previous, "selector_returned_nothing");
+    }
+    TALLIES.with(|tallies| {
+        if let Some((matched, missed)) = tallies.borrow_mut().last_mut() {
+            match (count, optional) {
+                (0, false) => *missed += 1,
+                (0, true) => (),
+                _ => *matched += 1
+            }
+        }
+    });
+    COLLECTOR.with(|collector| {
+        if let Some(counts) = collector.borrow_mut().as_mut() {
+            counts.push(SelectorCount { parser: parser.to_string(), selector: selector.to_string(), count, optional });
+        }
+    });
+}
+
+fn select_and_record<'a>(parent: ElementRef<'a>, key: &str, optional: bool) -> Vec<ElementRef<'a>> {
+    let (css, elements) = crate::selectors::select_with_match(parent, key);
+    // keys are "<parser>.<field>", so the prefix names the parser the selector belongs to
+    let parser = key.split('.').next().unwrap_or(key);
+    record(parser, &css, elements.len(), optional);
+    return elements;
+}
+
+// selects and records how many elements matched; an empty result means ESPN's markup has drifted
+pub fn tracked_select<'a>(parent: ElementRef<'a>, key: &str) -> Vec<ElementRef<'a>> {
+    return select_and_record(parent, key, false);
+}
+
+// for selectors that legitimately match nothing, e.g. live games or odds that aren't posted yet
+pub fn tracked_select_optional<'a>(parent: ElementRef<'a>, key: &str) -> Vec<ElementRef<'a>> {
+    return select_and_record(parent, key, true);
+}
+
+pub fn panic_message(error: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = error.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    return error.downcast_ref::<String>().cloned().unwrap_or_else(|| "parser panicked".to_string());
+}
+
+// runs one parser against a page; `produced_output` decides whether the parser returned anything useful
+pub fn check_parser<T>(parser: &str, url: &str, run: impl FnOnce() -> T, produced_output: impl Fn(&T) -> bool) -> (ParserHealth, Option<T>) {
+    COLLECTOR.with(|collector| *collector.borrow_mut() = Some(Vec::new()));
+    let result = panic::catch_unwind(AssertUnwindSafe(run));
+    let selectors = COLLECTOR.with(|collector| collector.borrow_mut().take()).unwrap_or_default();
+    let failing_selectors = selectors.iter()
+        .filter(|selector| selector.count == 0 && !selector.optional)
+        .map(|selector| selector.selector.to_string())
+        .collect::<Vec<String>>();
+    let (status, error, output) = match result {
+        Err(error) => (HealthStatus::Broken, Some(panic_message(error)), None),
+        Ok(output) if !produced_output(&output) => (HealthStatus::Degraded, Some("parser returned no data".to_string()), Some(output)),
+        Ok(output) if !failing_selectors.is_empty() => (HealthStatus::Degraded, None, Some(output)),
+        Ok(output) => (HealthStatus::Pass, None, Some(output))
+    };
+    let health = ParserHealth {
+        parser: parser.to_string(),
+        url: url.to_string(),
+        status,
+        failing_selectors,
+        selectors,
+        error
+    };
+    return (health, output);
+}
+
+pub fn scraper_health(parsers: Vec<ParserHealth>) -> ScraperHealth {
+    let status = parsers.iter().map(|parser| parser.status).fold(HealthStatus::Pass, |worst, status| {
+        if status > worst { status } else { worst }
+    });
+    return ScraperHealth { status, parsers };
+}
+
+// a page that failed to download is reported against every parser that needed it
+fn unreachable(parsers: &[&str], url: &str, error: String) -> Vec<ParserHealth> {
+    return parsers.iter().map(|parser| ParserHealth {
+        parser: parser.to_string(),
+        url: url.to_string(),
+        status: HealthStatus::Broken,
+        failing_selectors: vec![],
+        selectors: vec![],
+        error: Some(error.to_string())
+    }).collect();
+}
+
+async fn fetch_page(url: &str) -> Result<String, String> {
+    let response = upstream::get(url).await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("ESPN responded with {}", response.status()));
+    }
+    return response.text().await.map_err(|e| e.to_string());
+}
+
+// fetches a fresh copy of every page type and runs each parser against it
+pub async fn run_scraper_health(league: League, team_code: &str) -> ScraperHealth {
+    let mut parsers = Vec::new();
+
+    let teams_url = league.url("teams");
+    let mut teams = vec![];
+    match fetch_page(&teams_url).await {
+        Ok(html) => {
+            let (health, parsed) = check_parser("teams", &teams_url, || crate::get_teams(html, league), |teams| !teams.is_empty());
+            parsers.push(health);
+            teams = parsed.unwrap_or_default();
+        }
+        Err(error) => parsers.extend(unreachable(&["teams"], &teams_url, error))
+    }
+
+    let injuries_url = league.url("injuries");
+    match fetch_page(&injuries_url).await {
+        Ok(html) => parsers.push(check_parser("injuries", &injuries_url, || crate::injuries(html), |reports| !reports.is_empty()).0),
+        Err(error) => parsers.extend(unreachable(&["injuries"], &injuries_url, error))
+    }
+
+    let team_url = league.url(&format!("team/_/name/{}", team_code));
+    let mut latest_game_id = None;
+    let mut upcoming_game_id = None;
+    match fetch_page(&team_url).await {
+        Ok(html) => {
+            parsers.push(check_parser("previous_results", &team_url, || crate::get_completed_games_from_team_page_html(&html), |games| !games.is_empty()).0);
+            let (health, game_id) = check_parser("latest_game_id", &team_url, || crate::get_latest_game_id(html.to_string()), |game_id| !game_id.is_empty());
+            parsers.push(health);
+            latest_game_id = game_id;
+            let (health, game_id) = check_parser("upcoming_game_id", &team_url, || crate::find_upcoming_game_id_from_html(&html), |game_id| game_id.is_some());
+            parsers.push(health);
+            upcoming_game_id = game_id.flatten();
+            if upcoming_game_id.is_some() {
+                parsers.push(check_parser("upcoming_opponent", &team_url, || crate::get_upcoming_opponent_team_code(html.to_string(), league, &teams), |team_code| !team_code.is_empty()).0);
+            }
+        }
+        Err(error) => parsers.extend(unreachable(&["previous_results", "latest_game_id", "upcoming_game_id"], &team_url, error))
+    }
+
+    if let Some(game_id) = latest_game_id {
+        let box_score_url = league.url(&format!("boxscore/_/gameId/{}", game_id));
+        match fetch_page(&box_score_url).await {
+            Ok(html) => {
+                parsers.push(check_parser("game_header", &box_score_url, || crate::get_game_header(&html), |overview| overview.as_ref().is_some_and(|overview| !overview.event.home_team.id.is_empty())).0);
+                let (health, orientation) = check_parser("orientation", &box_score_url, || crate::get_orientation(&html, team_code), |home_or_away| home_or_away.is_some());
+                parsers.push(health);
+                let home_or_away = orientation.flatten().unwrap_or(crate::HomeOrAway::home);
+                parsers.push(check_parser("box_score", &box_score_url, || crate::find_latest_game_box(&html, home_or_away, league), |team_box| team_box.as_ref().is_some_and(|team_box| !team_box.player_records.is_empty())).0);
+            }
+            Err(error) => parsers.extend(unreachable(&["game_header", "orientation", "box_score"], &box_score_url, error))
+        }
+    }
+
+    if let Some(game_id) = upcoming_game_id {
+        let game_url = league.url(&format!("game/_/gameId/{}", game_id));
+        match fetch_page(&game_url).await {
+            Ok(html) => {
+                parsers.push(check_parser("odds", &game_url, || crate::get_odds_for_game_html(html.to_string()), |odds| odds.is_some()).0);
+                parsers.push(check_parser("game_info", &game_url, || crate::preview::get_game_info_from_html(&game_id, &html), |game| game.is_some()).0);
+                parsers.push(check_parser("season_series", &game_url, || crate::preview::get_season_series_from_html(&html), |_| true).0);
+            }
+            Err(error) => parsers.extend(unreachable(&["odds", "game_info", "season_series"], &game_url, error))
+        }
+    }
+
+    return scraper_health(parsers);
+}
+
+#[test]
+fn check_parser_test() {
+    let contents = std::fs::read_to_string("./test-data/teams-page.html").unwrap();
+    let (health, teams) = check_parser("teams", "teams-page.html", || crate::get_teams(contents.to_string(), League::Nba), |teams| !teams.is_empty());
+    assert_eq!(teams.unwrap().len(), 30);
+    assert_eq!(health.status, HealthStatus::Pass);
+    assert_eq!(health.selectors[0].parser, "teams");
+    assert_eq!(health.selectors[0].selector, "section.TeamLinks");
+    assert_eq!(health.selectors[0].count, 30);
+
+    let injuries = std::fs::read_to_string("./test-data/injuries.html").unwrap();
+    let (health, _) = check_parser("teams", "injuries.html", || crate::get_teams(injuries.to_string(), League::Nba), |teams| !teams.is_empty());
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert_eq!(health.failing_selectors, vec!["section.TeamLinks".to_string()]);
+
+    let (health, team_box) = check_parser("box_score", "teams-page.html", || crate::get_latest_game_box(&contents, crate::HomeOrAway::home, League::Nba), |_| true);
+    assert_eq!(team_box.is_none(), true);
+    assert_eq!(health.status, HealthStatus::Broken);
+    assert_eq!(health.failing_selectors, vec![".gamepackage-home-wrap table".to_string()]);
+
+    let report = scraper_health(vec![health]);
+    assert_eq!(report.status, HealthStatus::Broken);
+}
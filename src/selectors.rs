@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use scraper::{ElementRef, Selector};
+use serde::{Serialize, Deserialize};
+
+// compiled in so parsers and tests work without a selectors file on disk
+const DEFAULT_SELECTORS: &str = include_str!("../selectors.json");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelectorsConfig {
+    #[serde(default = "default_selectors_path")]
+    pub selectors_path: String,
+    #[serde(default = "default_selectors_reload_interval")]
+    pub selectors_reload_interval: u64
+}
+
+fn default_selectors_path() -> String {
+    return "selectors.json".to_string();
+}
+
+fn default_selectors_reload_interval() -> u64 {
+    return 5;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SelectorFile {
+    version: u32,
+    selectors: HashMap<String, Vec<String>>
+}
+
+// each field keeps its selectors in priority order; the first is the primary, the rest are fallbacks
+pub struct SelectorSet {
+    pub version: u32,
+    selectors: HashMap<String, Vec<(String, Selector)>>
+}
+
+impl SelectorSet {
+    pub fn parse(contents: &str) -> Result<SelectorSet, String> {
+        let file: SelectorFile = serde_json::from_str(contents).map_err(|e| format!("invalid selector file: {}", e))?;
+        let mut selectors = HashMap::new();
+        for (key, css_list) in file.selectors {
+            if css_list.is_empty() {
+                return Err(format!("{} has no selectors", key));
+            }
+            let mut compiled = Vec::new();
+            for css in css_list {
+                let selector = Selector::parse(&css).map_err(|_| format!("{} has an invalid selector: {}", key, css))?;
+                compiled.push((css, selector));
+            }
+            selectors.insert(key, compiled);
+        }
+        let set = SelectorSet { version: file.version, selectors };
+        // a patched file must still cover every field the parsers ask for
+        if let Ok(defaults) = serde_json::from_str::<SelectorFile>(DEFAULT_SELECTORS) {
+            let mut missing = defaults.selectors.keys().filter(|key| !set.selectors.contains_key(*key)).cloned().collect::<Vec<String>>();
+            missing.sort();
+            if !missing.is_empty() {
+                return Err(format!("missing selectors: {}", missing.join(", ")));
+            }
+        }
+        return Ok(set);
+    }
+
+    fn get(&self, key: &str) -> &[(String, Selector)] {
+        return self.selectors.get(key).map(|selectors| selectors.as_slice()).unwrap_or_else(|| panic!("no selector configured for {}", key));
+    }
+
+    // tries each selector for the field in order and returns the first one that matches, with the css that matched
+    pub fn select_with_match<'a>(&self, parent: ElementRef<'a>, key: &str) -> (String, Vec<ElementRef<'a>>) {
+        let selectors = self.get(key);
+        for (css, selector) in selectors {
+            let elements = parent.select(selector).collect::<Vec<ElementRef>>();
+            if !elements.is_empty() {
+                return (css.to_string(), elements);
+            }
+        }
+        return (selectors[0].0.to_string(), vec![]);
+    }
+}
+
+fn current_set() -> &'static RwLock<Arc<SelectorSet>> {
+    static CURRENT: OnceLock<RwLock<Arc<SelectorSet>>> = OnceLock::new();
+    return CURRENT.get_or_init(|| RwLock::new(Arc::new(SelectorSet::parse(DEFAULT_SELECTORS).unwrap())));
+}
+
+pub fn current() -> Arc<SelectorSet> {
+    return current_set().read().unwrap().clone();
+}
+
+pub fn install(set: SelectorSet) {
+    *current_set().write().unwrap() = Arc::new(set);
+}
+
+pub fn load(path: &PathBuf) -> Result<SelectorSet, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    return SelectorSet::parse(&contents);
+}
+
+pub fn select_with_match<'a>(parent: ElementRef<'a>, key: &str) -> (String, Vec<ElementRef<'a>>) {
+    return current().select_with_match(parent, key);
+}
+
+pub fn select<'a>(parent: ElementRef<'a>, key: &str) -> Vec<ElementRef<'a>> {
+    return select_with_match(parent, key).1;
+}
+
+pub fn select_first<'a>(parent: ElementRef<'a>, key: &str) -> Option<ElementRef<'a>> {
+    return select(parent, key).into_iter().next();
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    return fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+}
+
+// polls the file's modification time and swaps in the new selectors once they validate
+pub fn watch(path: PathBuf, interval: u64) {
+    tokio::spawn(async move {
+        let mut last_modified = modified(&path);
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        loop {
+            ticker.tick().await;
+            let now_modified = modified(&path);
+            if now_modified == last_modified {
+                continue;
+            }
+            last_modified = now_modified;
+            match load(&path) {
+                Ok(set) => {
+                    tracing::info!(path = %path.display(), version = set.version, "selectors_reloaded");
+                    install(set);
+                }
+                Err(error) => tracing::error!(path = %path.display(), error = error.as_str(), "selectors_rejected")
+            }
+        }
+    });
+}
+
+#[test]
+fn default_selectors_are_valid_test() {
+    let set = SelectorSet::parse(DEFAULT_SELECTORS).unwrap();
+    assert_eq!(set.version, 1);
+    assert_eq!(set.get("teams.team_links")[0].0, "section.TeamLinks");
+}
+
+#[test]
+fn selector_validation_test() {
+    let invalid = r#"{"version": 2, "selectors": {"teams.team_links": ["section..TeamLinks"]}}"#;
+    assert_eq!(SelectorSet::parse(invalid).err().unwrap(), "teams.team_links has an invalid selector: section..TeamLinks");
+    let incomplete = r#"{"version": 2, "selectors": {"teams.team_links": ["section.TeamLinks"]}}"#;
+    assert_eq!(SelectorSet::parse(incomplete).err().unwrap().starts_with("missing selectors: "), true);
+}
+
+#[test]
+fn select_falls_back_test() {
+    let mut file: SelectorFile = serde_json::from_str(DEFAULT_SELECTORS).unwrap();
+    file.selectors.insert("teams.team_links".to_string(), vec!["section.RenamedTeamLinks".to_string(), "section.TeamLinks".to_string()]);
+    let set = SelectorSet::parse(&serde_json::to_string(&file).unwrap()).unwrap();
+    let contents = fs::read_to_string("./test-data/teams-page.html").unwrap();
+    let fragment = scraper::Html::parse_document(&contents);
+    let (matched, team_links) = set.select_with_match(fragment.root_element(), "teams.team_links");
+    assert_eq!(matched, "section.TeamLinks");
+    assert_eq!(team_links.len(), 30);
+}
@@ -0,0 +1,286 @@
+use std::sync::OnceLock;
+
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{GameOdds, PlayerInjury, Team, TeamBox, TeamInjuryReport};
+use crate::embedded_json;
+use crate::league::League;
+use crate::upstream;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    Html,
+    SiteApi
+}
+
+impl Default for DataSource {
+    fn default() -> Self {
+        return DataSource::Html;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Teams,
+    Injuries,
+    BoxScore,
+    Odds
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DataSources {
+    #[serde(default)]
+    pub teams: DataSource,
+    #[serde(default)]
+    pub injuries: DataSource,
+    #[serde(default)]
+    pub box_score: DataSource,
+    #[serde(default)]
+    pub odds: DataSource
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataSourceConfig {
+    #[serde(default)]
+    pub data_sources: DataSources
+}
+
+fn configured() -> &'static OnceLock<DataSources> {
+    static SOURCES: OnceLock<DataSources> = OnceLock::new();
+    return &SOURCES;
+}
+
+pub fn configure(sources: DataSources) {
+    let _ = configured().set(sources);
+}
+
+pub fn source(resource: Resource) -> DataSource {
+    let sources = match configured().get() {
+        Some(sources) => sources,
+        None => return DataSource::Html
+    };
+    return match resource {
+        Resource::Teams => sources.teams,
+        Resource::Injuries => sources.injuries,
+        Resource::BoxScore => sources.box_score,
+        Resource::Odds => sources.odds
+    };
+}
+
+async fn get_json(league: League, path: &str) -> Option<Value> {
+    let response = upstream::get(format!("{}/apis/site/v2/sports/basketball/{}{}", upstream::api_base_url(), league.site_api_slug(), path)).await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    return response.json::<Value>().await.ok();
+}
+
+fn fallback(resource: Resource) {
+    tracing::warn!(resource = ?resource, "site_api_fallback");
+}
+
+fn text(value: &Value) -> String {
+    return value.as_str().unwrap_or("").to_string();
+}
+
+pub fn teams_from_json(teams: &Value) -> Option<Vec<Team>> {
+    let teams = teams["sports"][0]["leagues"][0]["teams"].as_array()?.iter()
+        .filter_map(|entry| Some(Team {
+            id: entry["team"]["abbreviation"].as_str()?.to_lowercase(),
+            full_name: text(&entry["team"]["displayName"])
+        }))
+        .collect::<Vec<Team>>();
+    return match teams.is_empty() {
+        true => None,
+        false => Some(teams)
+    };
+}
+
+// the injuries page shows dates as "Jan 9"
+fn injury_date(date: &str) -> String {
+    return NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%MZ")
+        .map(|date| date.format("%b %-d").to_string())
+        .unwrap_or_else(|_| date.to_string());
+}
+
+// team codes aren't in this feed; `get_injuries_with_team_code` matches them up by team name like it does for the page.
+// a team entry without an injuries list counts as nobody hurt rather than discarding the rest of the feed
+pub fn injuries_from_json(injuries: &Value) -> Option<Vec<TeamInjuryReport>> {
+    let mut team_injury_reports = Vec::new();
+    for team in injuries["injuries"].as_array()? {
+        team_injury_reports.push(TeamInjuryReport {
+            team_code: "".to_string(),
+            team_name: text(&team["displayName"]),
+            injuries: team["injuries"].as_array().map(|injuries| injuries.iter().map(|injury| PlayerInjury {
+                name: text(&injury["athlete"]["displayName"]),
+                date: injury_date(injury["date"].as_str().unwrap_or("")),
+                position: text(&injury["athlete"]["position"]["abbreviation"]),
+                status: text(&injury["status"]),
+                description: crate::injury_description(injury["longComment"].as_str().unwrap_or(""))
+            }).collect()).unwrap_or_default()
+        });
+    }
+    return Some(team_injury_reports);
+}
+
+// the most recently completed game; live games count too so the box score follows along
+pub fn latest_game_id_from_schedule(schedule: &Value) -> Option<String> {
+    let mut events = schedule["events"].as_array()?.iter()
+        .filter(|event| {
+            let status = &event["competitions"][0]["status"]["type"];
+            status["completed"].as_bool() == Some(true) || status["state"].as_str() == Some("in")
+        })
+        .collect::<Vec<&Value>>();
+    // ISO 8601 dates sort correctly as strings
+    events.sort_by_key(|event| event["date"].as_str().unwrap_or(""));
+    return events.last().map(|event| text(&event["id"]));
+}
+
+// the summary feed has the same shape as the `espn.gamepackage.data` blob on game pages
+pub fn team_box_from_summary(summary: &Value, team_code: &str, league: League) -> Option<TeamBox> {
+    let home_or_away = embedded_json::orientation(summary, team_code)?;
+    return embedded_json::team_box(summary, &home_or_away, league);
+}
+
+pub async fn get_teams(league: League) -> Option<Vec<Team>> {
+    let teams = get_json(league, "/teams").await.as_ref().and_then(teams_from_json);
+    if teams.is_none() {
+        fallback(Resource::Teams);
+    }
+    return teams;
+}
+
+pub async fn get_injuries(league: League) -> Option<Vec<TeamInjuryReport>> {
+    let injuries = get_json(league, "/injuries").await.as_ref().and_then(injuries_from_json);
+    if injuries.is_none() {
+        fallback(Resource::Injuries);
+    }
+    return injuries;
+}
+
+pub async fn get_team_box_score(league: League, team_code: &str) -> Option<TeamBox> {
+    let game_id = get_json(league, &format!("/teams/{}/schedule", team_code)).await.as_ref().and_then(latest_game_id_from_schedule);
+    let team_box = match game_id {
+        Some(game_id) => get_json(league, &format!("/summary?event={}", game_id)).await.as_ref().and_then(|summary| team_box_from_summary(summary, team_code, league)),
+        None => None
+    };
+    if team_box.is_none() {
+        fallback(Resource::BoxScore);
+    }
+    return team_box;
+}
+
+// Some(None) means ESPN answered but has no lines posted, which is a real answer rather than a reason to fall back
+pub async fn get_odds_for_game(league: League, game_id: &str) -> Option<Option<GameOdds>> {
+    let summary = get_json(league, &format!("/summary?event={}", game_id)).await;
+    if summary.is_none() {
+        fallback(Resource::Odds);
+    }
+    return summary.map(|summary| embedded_json::game_odds(&summary));
+}
+
+#[cfg(test)]
+fn fixture(name: &str) -> Value {
+    return serde_json::from_str(&std::fs::read_to_string(format!("./test-data/{}", name)).unwrap()).unwrap();
+}
+
+#[test]
+fn injuries_from_json_description_test() {
+    let feed = json!({"injuries": [{"displayName": "Toronto Raptors", "injuries": [{
+        "athlete": {"displayName": "Otto Porter Jr.", "position": {"abbreviation": "F"}},
+        "date": "2022-01-09T18:30Z",
+        "status": "Out",
+        "longComment": "Porter (back) is out, Blake Murphy of The Athletic reports."
+    }]}]});
+    let injury = &injuries_from_json(&feed).unwrap()[0].injuries[0];
+    assert_eq!(injury.date, "Jan 9");
+    assert_eq!(injury.description, "Porter (back) is out, Blake Murphy of SportsNet reports.");
+
+    // one team without an injuries list doesn't cost the others theirs
+    let feed = json!({"injuries": [{"displayName": "Washington Wizards"}, feed["injuries"][0].clone()]});
+    let reports = injuries_from_json(&feed).unwrap();
+    assert_eq!((reports[0].injuries.len(), reports[1].injuries.len()), (0, 1));
+}
+
+// none of the feeds have responses recorded from site.api.espn.com yet. the summary fixtures are the summary feed the
+// saved game pages embed, which is the same document; the teams, schedule and injuries feeds are checked with inline
+// feeds of the same shape until they're recorded with `fixture_record_path`
+#[test]
+fn odds_from_summary_test() {
+    let odds = embedded_json::game_odds(&fixture("site-api-summary-401360432.json")).unwrap();
+    assert_eq!(odds.away_team, "phx");
+    assert_eq!(odds.home_moneyline, "+155");
+    assert_eq!(odds.providers.len(), 3);
+    assert_eq!(embedded_json::game_odds(&fixture("site-api-summary-401360452.json")).is_none(), true);
+}
+
+#[test]
+fn teams_from_json_matches_teams_page_test() {
+    let feed = json!({"sports": [{"leagues": [{"teams": [
+        {"team": {"abbreviation": "TOR", "displayName": "Toronto Raptors"}},
+        {"team": {"abbreviation": "GS", "displayName": "Golden State Warriors"}},
+        {"team": {"abbreviation": "UTAH", "displayName": "Utah Jazz"}},
+        {"team": {"displayName": "No Abbreviation"}}
+    ]}]}]});
+    let teams = teams_from_json(&feed).unwrap();
+    assert_eq!(teams.len(), 3);
+    // the same codes and names as the teams page, so either source can back /teams
+    let teams_page = crate::get_teams(std::fs::read_to_string("./test-data/teams-page.html").unwrap(), League::Nba);
+    for team in &teams {
+        assert_eq!(teams_page.iter().any(|page_team| page_team.id == team.id && page_team.full_name == team.full_name), true, "{:?}", team);
+    }
+    assert_eq!(teams_from_json(&json!({"sports": [{"leagues": [{"teams": []}]}]})).is_none(), true);
+}
+
+#[test]
+fn latest_game_id_from_schedule_test() {
+    let event = |id: &str, date: &str, state: &str, completed: bool| json!({
+        "id": id, "date": date, "competitions": [{"status": {"type": {"state": state, "completed": completed}}}]
+    });
+    let schedule = json!({"events": [
+        event("401360432", "2022-01-11T00:30Z", "pre", false),
+        event("401360400", "2022-01-07T00:30Z", "post", true),
+        event("401360415", "2022-01-09T00:30Z", "post", true)
+    ]});
+    assert_eq!(latest_game_id_from_schedule(&schedule), Some("401360415".to_string()));
+    // a live game is the latest one
+    let live = json!({"events": [event("401360415", "2022-01-09T00:30Z", "post", true), event("401360432", "2022-01-11T00:30Z", "in", false)]});
+    assert_eq!(latest_game_id_from_schedule(&live), Some("401360432".to_string()));
+    assert_eq!(latest_game_id_from_schedule(&json!({"events": [event("401360432", "2022-01-11T00:30Z", "pre", false)]})), None);
+}
+
+#[test]
+fn team_box_from_summary_test() {
+    // taken before tip-off, so there are teams but no players yet
+    let summary = fixture("site-api-summary-401360432.json");
+    assert_eq!(team_box_from_summary(&summary, "tor", League::Nba).is_none(), true);
+    // the sides agree with the game page's
+    let game_page = std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap();
+    let game = crate::preview::get_game_info_from_html("401360432", &game_page).unwrap();
+    assert_eq!(embedded_json::orientation(&summary, &game.away_team.team_code).unwrap().to_string(), "away");
+    assert_eq!(embedded_json::orientation(&summary, &game.home_team.team_code).unwrap().to_string(), "home");
+
+    // once the game is over the summary has each side's players
+    let completed = json!({
+        "header": {"id": "401307733", "competitions": [{"competitors": [
+            {"homeAway": "home", "id": "28", "score": "103", "team": {"abbreviation": "TOR", "name": "Raptors", "logos": [{"href": "https://a.espncdn.com/i/teamlogos/nba/500/tor.png"}]}},
+            {"homeAway": "away", "id": "17", "score": "116", "team": {"abbreviation": "BKN", "name": "Nets", "logos": [{"href": "https://a.espncdn.com/i/teamlogos/nba/500/bkn.png"}]}}
+        ]}]},
+        "boxscore": {"players": [
+            {"team": {"id": "28"}, "statistics": [{"names": ["MIN", "PTS"], "athletes": [
+                {"starter": true, "didNotPlay": false, "athlete": {"id": "3134908", "shortName": "P. Siakam", "position": {"abbreviation": "PF"}}, "stats": ["36", "23"]}
+            ]}]},
+            {"team": {"id": "17"}, "statistics": [{"names": ["MIN", "PTS"], "athletes": [
+                {"starter": true, "didNotPlay": false, "athlete": {"id": "3202", "shortName": "K. Durant", "position": {"abbreviation": "SF"}}, "stats": ["38", "33"]}
+            ]}]}
+        ]}
+    });
+    let tor = team_box_from_summary(&completed, "tor", League::Nba).unwrap();
+    assert_eq!((tor.player_records[0].player.first_initial_and_last_name.as_str(), tor.player_records[0].alignment.as_str()), ("P. Siakam", "home"));
+    let bkn = team_box_from_summary(&completed, "bkn", League::Nba).unwrap();
+    assert_eq!((bkn.player_records[0].player.points.as_str(), bkn.player_records[0].alignment.as_str()), ("33", "away"));
+}
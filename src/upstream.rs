@@ -0,0 +1,431 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderName, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::{Client, IntoUrl, Response, StatusCode, Url};
+use serde::{Serialize, Deserialize};
+use tracing::field::{self, Empty};
+use tracing::{Instrument, Span};
+
+use crate::fixtures;
+use crate::metrics;
+use crate::page_cache::{self, CacheEntry};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpstreamConfig {
+    #[serde(default = "default_upstream_user_agent")]
+    pub upstream_user_agent: String,
+    // seconds
+    #[serde(default = "default_upstream_connect_timeout")]
+    pub upstream_connect_timeout: u64,
+    // seconds; reqwest 0.11 only has a whole-request timeout, which bounds a stalled read
+    #[serde(default = "default_upstream_read_timeout")]
+    pub upstream_read_timeout: u64,
+    #[serde(default = "default_upstream_retries")]
+    pub upstream_retries: u32,
+    // milliseconds before the first retry, doubling after each one
+    #[serde(default = "default_upstream_backoff")]
+    pub upstream_backoff: u64,
+    // shared by every outbound request; 0 turns the cap off
+    #[serde(default = "default_upstream_requests_per_second")]
+    pub upstream_requests_per_second: f64,
+    // seconds; a 429 asking us to wait longer than this is handed back instead of retried
+    #[serde(default = "default_upstream_max_retry_after")]
+    pub upstream_max_retry_after: u64,
+    // where pages and the site API are fetched from; tests point both at a local stand-in
+    #[serde(default = "default_espn_base_url")]
+    pub espn_base_url: String,
+    #[serde(default = "default_espn_api_base_url")]
+    pub espn_api_base_url: String
+}
+
+fn default_upstream_user_agent() -> String {
+    return format!("espn-nba-scraper-api/{}", env!("CARGO_PKG_VERSION"));
+}
+
+fn default_upstream_connect_timeout() -> u64 {
+    return 5;
+}
+
+fn default_upstream_read_timeout() -> u64 {
+    return 20;
+}
+
+fn default_upstream_retries() -> u32 {
+    return 3;
+}
+
+fn default_upstream_backoff() -> u64 {
+    return 250;
+}
+
+fn default_upstream_requests_per_second() -> f64 {
+    return 5.0;
+}
+
+fn default_upstream_max_retry_after() -> u64 {
+    return 30;
+}
+
+fn default_espn_base_url() -> String {
+    return "https://www.espn.com".to_string();
+}
+
+fn default_espn_api_base_url() -> String {
+    return "https://site.api.espn.com".to_string();
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        return UpstreamConfig {
+            upstream_user_agent: default_upstream_user_agent(),
+            upstream_connect_timeout: default_upstream_connect_timeout(),
+            upstream_read_timeout: default_upstream_read_timeout(),
+            upstream_retries: default_upstream_retries(),
+            upstream_backoff: default_upstream_backoff(),
+            upstream_requests_per_second: default_upstream_requests_per_second(),
+            upstream_max_retry_after: default_upstream_max_retry_after(),
+            espn_base_url: default_espn_base_url(),
+            espn_api_base_url: default_espn_api_base_url()
+        };
+    }
+}
+
+fn configured() -> &'static OnceLock<UpstreamConfig> {
+    static CONFIG: OnceLock<UpstreamConfig> = OnceLock::new();
+    return &CONFIG;
+}
+
+// has to run before the first request; the CLI never calls it and gets the defaults
+pub fn configure(config: UpstreamConfig) {
+    let _ = configured().set(config);
+}
+
+fn config() -> &'static UpstreamConfig {
+    return configured().get_or_init(UpstreamConfig::default);
+}
+
+// without a trailing slash, e.g. "https://www.espn.com"
+pub fn base_url() -> &'static str {
+    return config().espn_base_url.trim_end_matches('/');
+}
+
+pub fn api_base_url() -> &'static str {
+    return config().espn_api_base_url.trim_end_matches('/');
+}
+
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    return CLIENT.get_or_init(|| {
+        let config = config();
+        Client::builder()
+            .user_agent(config.upstream_user_agent.to_string())
+            .connect_timeout(Duration::from_secs(config.upstream_connect_timeout))
+            .timeout(Duration::from_secs(config.upstream_read_timeout))
+            .build()
+            .unwrap()
+    });
+}
+
+const REACHABILITY_WINDOW: usize = 20;
+
+// whether the recent fetches got an answer from ESPN (anything but a 5xx or no response at all)
+#[derive(Debug, Clone, Default)]
+pub struct Reachability {
+    answered: VecDeque<bool>,
+    pub last_answered_at: Option<DateTime<Utc>>,
+    pub last_failed_at: Option<DateTime<Utc>>
+}
+
+impl Reachability {
+    fn record(&mut self, answered: bool, at: DateTime<Utc>) {
+        if self.answered.len() == REACHABILITY_WINDOW {
+            self.answered.pop_front();
+        }
+        self.answered.push_back(answered);
+        match answered {
+            true => self.last_answered_at = Some(at),
+            false => self.last_failed_at = Some(at)
+        }
+    }
+
+    // out of the last REACHABILITY_WINDOW
+    pub fn fetches(&self) -> usize {
+        return self.answered.len();
+    }
+
+    pub fn failures(&self) -> usize {
+        return self.answered.iter().filter(|answered| !**answered).count();
+    }
+
+    // one timeout among mostly good fetches isn't an outage, so this takes more than half of the window failing
+    pub fn reachable(&self) -> bool {
+        return self.fetches() > 0 && self.failures() * 2 <= self.fetches();
+    }
+}
+
+fn reachability_window() -> &'static Mutex<Reachability> {
+    static REACHABILITY: OnceLock<Mutex<Reachability>> = OnceLock::new();
+    return REACHABILITY.get_or_init(|| Mutex::new(Reachability::default()));
+}
+
+pub fn reachability() -> Reachability {
+    return reachability_window().lock().unwrap().clone();
+}
+
+fn next_slot() -> &'static Mutex<Instant> {
+    static NEXT_SLOT: OnceLock<Mutex<Instant>> = OnceLock::new();
+    return NEXT_SLOT.get_or_init(|| Mutex::new(Instant::now()));
+}
+
+// books the next outbound slot and returns how long to wait for it
+fn reserve(next_slot: &mut Instant, now: Instant, interval: Duration) -> Duration {
+    let slot = (*next_slot).max(now);
+    *next_slot = slot + interval;
+    return slot - now;
+}
+
+async fn throttle(config: &UpstreamConfig) {
+    if config.upstream_requests_per_second <= 0.0 {
+        return;
+    }
+    let interval = Duration::from_secs_f64(1.0 / config.upstream_requests_per_second);
+    let wait = reserve(&mut next_slot().lock().unwrap(), Instant::now(), interval);
+    tokio::time::sleep(wait).await;
+}
+
+// ESPN rate limits by address, so a 429 holds back every outbound request, not just the one that got it
+fn pause(wait: Duration) {
+    let mut next_slot = next_slot().lock().unwrap();
+    *next_slot = (*next_slot).max(Instant::now() + wait);
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    return Some((date.with_timezone(&Utc) - now).to_std().unwrap_or(Duration::ZERO));
+}
+
+fn backoff_ceiling(attempt: u32, base: u64) -> u64 {
+    return base.saturating_mul(2u64.saturating_pow(attempt));
+}
+
+// "full jitter": a random wait up to the exponential ceiling, so callers that failed together don't retry together
+fn backoff(attempt: u32, base: u64) -> Duration {
+    return Duration::from_millis(rand::thread_rng().gen_range(0..=backoff_ceiling(attempt, base)));
+}
+
+// the retry loop; a cached entry turns the request into a conditional GET
+async fn fetch(url: &Url, cached: Option<&CacheEntry>) -> reqwest::Result<Response> {
+    let config = config();
+    let mut attempt = 0;
+    loop {
+        throttle(config).await;
+        let mut request = client().get(url.clone());
+        if let Some(etag) = cached.and_then(|entry| entry.etag.as_ref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = cached.and_then(|entry| entry.last_modified.as_ref()) {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        let result = request.send().await;
+        let wait = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                let requested = response.headers().get(RETRY_AFTER).and_then(|value| value.to_str().ok()).and_then(|value| retry_after(value, Utc::now()));
+                match requested {
+                    Some(wait) if wait <= Duration::from_secs(config.upstream_max_retry_after) => {
+                        pause(wait);
+                        Some(Duration::ZERO)
+                    }
+                    Some(_) => None,
+                    None => Some(backoff(attempt, config.upstream_backoff))
+                }
+            }
+            Ok(response) if response.status().is_server_error() => Some(backoff(attempt, config.upstream_backoff)),
+            Ok(_) => None,
+            Err(error) if error.is_timeout() || error.is_connect() => Some(backoff(attempt, config.upstream_backoff)),
+            Err(_) => None
+        };
+        match wait {
+            Some(wait) if attempt < config.upstream_retries => {
+                let reason = match &result {
+                    Ok(response) => response.status().to_string(),
+                    Err(error) => error.to_string()
+                };
+                tracing::warn!(url = url.as_str(), attempt = attempt + 1, reason = reason.as_str(), "upstream_retry");
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            _ => return result
+        }
+    }
+}
+
+fn header(response: &Response, name: HeaderName) -> Option<String> {
+    return response.headers().get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string());
+}
+
+// rebuilds a response from a body already read, so callers can't tell it apart from a fresh one
+fn buffered_response(content_type: Option<&str>, body: Vec<u8>) -> Response {
+    let mut response = http::Response::builder().status(StatusCode::OK);
+    if let Some(content_type) = content_type {
+        response = response.header(CONTENT_TYPE, content_type);
+    }
+    return Response::from(response.body(body).unwrap());
+}
+
+fn stale(entry: &CacheEntry, body: Vec<u8>, reason: String) -> Response {
+    Span::current().record("cache", "stale");
+    metrics::page_cache("stale");
+    tracing::warn!(url = entry.url.as_str(), fetched_at = %entry.fetched_at, reason = reason.as_str(), "upstream_stale");
+    return buffered_response(entry.content_type.as_deref(), body);
+}
+
+// a drop-in for `reqwest::get` that shares one client, throttles, and retries 5xx, timeouts and 429s.
+// with the page cache on, successful bodies are stored, 304s are answered from disk, and a cached copy stands in
+// when ESPN still fails after the retries (stale-if-error). with the fixture recorder on, every live page is saved
+// as a fixture too. otherwise the last response or error comes back as-is.
+// each call is a `fetch` span with the URL and page kind, plus the status, size and cache outcome once it's back
+pub async fn get<U: IntoUrl>(url: U) -> reqwest::Result<Response> {
+    let url = url.into_url()?;
+    let page = fixtures::describe(url.as_str()).map(|fixture| fixture.kind);
+    let span = tracing::info_span!("fetch", url = url.as_str(), page = page.as_ref().map(field::debug), status = Empty, bytes = Empty, cache = Empty, error = Empty);
+    let started = Instant::now();
+    let result = get_through_cache(url).instrument(span.clone()).await;
+    metrics::fetched(page, result.as_ref().ok().map(|response| response.status().as_u16()), started.elapsed());
+    let reachable = result.as_ref().map(|response| !response.status().is_server_error()).unwrap_or(false);
+    reachability_window().lock().unwrap().record(reachable, Utc::now());
+    match &result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            span.record("bytes", response.content_length());
+        }
+        Err(error) => {
+            span.record("error", error.to_string().as_str());
+        }
+    }
+    return result;
+}
+
+// the page cache and fixture recorder are plain files, so they're read and written off the async workers
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    return tokio::task::spawn_blocking(work).await.unwrap();
+}
+
+async fn get_through_cache(url: Url) -> reqwest::Result<Response> {
+    let cache = page_cache::cache();
+    let recorder = fixtures::recorder();
+    if cache.is_none() && recorder.is_none() {
+        return fetch(&url, None).await;
+    }
+    let cached = match cache {
+        Some(cache) => {
+            let url = url.to_string();
+            blocking(move || cache.get(&url)).await
+        }
+        None => None
+    };
+    let result = fetch(&url, cached.as_ref().map(|(entry, _)| entry)).await;
+    let (content_type, body) = match (result, cached) {
+        (Ok(response), Some((entry, body))) if response.status() == StatusCode::NOT_MODIFIED => {
+            let cache = cache.unwrap();
+            let revalidated = entry.clone();
+            blocking(move || cache.revalidated(&revalidated)).await;
+            Span::current().record("cache", "revalidated");
+            metrics::page_cache("hit");
+            (entry.content_type, body)
+        }
+        (Ok(response), cached) if response.status().is_success() => {
+            let content_type = header(&response, CONTENT_TYPE);
+            let etag = header(&response, ETAG);
+            let last_modified = header(&response, LAST_MODIFIED);
+            match (response.bytes().await, cached) {
+                (Ok(body), _) => {
+                    if let Some(cache) = cache {
+                        let (url, content_type, body) = (url.to_string(), content_type.clone(), body.clone());
+                        blocking(move || cache.put(&url, content_type, etag, last_modified, &body)).await;
+                        Span::current().record("cache", "stored");
+                        metrics::page_cache("miss");
+                    }
+                    (content_type, body.to_vec())
+                }
+                (Err(error), Some((entry, body))) => return Ok(stale(&entry, body, error.to_string())),
+                (Err(error), None) => return Err(error)
+            }
+        }
+        (Ok(response), Some((entry, body))) if response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS => {
+            return Ok(stale(&entry, body, response.status().to_string()));
+        }
+        (Err(error), Some((entry, body))) => return Ok(stale(&entry, body, error.to_string())),
+        (result, _) => return result
+    };
+    let body = match recorder {
+        Some(recorder) => {
+            let url = url.to_string();
+            blocking(move || {
+                recorder.record(&url, &body);
+                body
+            }).await
+        }
+        None => body
+    };
+    return Ok(buffered_response(content_type.as_deref(), body));
+}
+
+#[test]
+fn reachability_test() {
+    let now = Utc::now();
+    let mut reachability = Reachability::default();
+    assert_eq!(reachability.reachable(), false);
+    reachability.record(false, now);
+    assert_eq!(reachability.reachable(), false);
+    reachability.record(true, now);
+    assert_eq!(reachability.reachable(), true);
+    for _ in 0..REACHABILITY_WINDOW {
+        reachability.record(false, now);
+    }
+    assert_eq!((reachability.fetches(), reachability.failures(), reachability.reachable()), (REACHABILITY_WINDOW, REACHABILITY_WINDOW, false));
+    for _ in 0..REACHABILITY_WINDOW / 2 {
+        reachability.record(true, now);
+    }
+    assert_eq!(reachability.reachable(), true);
+}
+
+#[test]
+fn reserve_spaces_requests_test() {
+    let start = Instant::now();
+    let interval = Duration::from_millis(200);
+    let mut next_slot = start;
+    assert_eq!(reserve(&mut next_slot, start, interval), Duration::ZERO);
+    assert_eq!(reserve(&mut next_slot, start, interval), Duration::from_millis(200));
+    assert_eq!(reserve(&mut next_slot, start, interval), Duration::from_millis(400));
+    // an idle limiter doesn't bank slots for a later burst
+    let later = start + Duration::from_secs(10);
+    assert_eq!(reserve(&mut next_slot, later, interval), Duration::ZERO);
+    assert_eq!(reserve(&mut next_slot, later, interval), Duration::from_millis(200));
+}
+
+#[test]
+fn retry_after_test() {
+    let now = DateTime::parse_from_rfc3339("2021-10-21T07:28:00Z").unwrap().with_timezone(&Utc);
+    assert_eq!(retry_after("120", now), Some(Duration::from_secs(120)));
+    assert_eq!(retry_after("Thu, 21 Oct 2021 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+    assert_eq!(retry_after("Thu, 21 Oct 2021 07:27:00 GMT", now), Some(Duration::ZERO));
+    assert_eq!(retry_after("soon", now), None);
+}
+
+#[test]
+fn backoff_test() {
+    assert_eq!(backoff_ceiling(0, 250), 250);
+    assert_eq!(backoff_ceiling(3, 250), 2000);
+    assert_eq!(backoff_ceiling(80, 250), u64::MAX);
+    for attempt in 0..4 {
+        assert_eq!(backoff(attempt, 250) <= Duration::from_millis(backoff_ceiling(attempt, 250)), true);
+    }
+}
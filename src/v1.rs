@@ -0,0 +1,157 @@
+use rocket::{get, routes, Route, State};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+use crate::{ats, matchups, preview};
+use crate::api_keys::ApiClient;
+use crate::{GameOdds, GameResult, Team, TeamBox, TeamInjuryReport, UpcomingLineups};
+use crate::export::{self, ExportFormat, Exported};
+use crate::league::League;
+use crate::odds_history::{OddsHistory, OddsHistoryStore};
+
+// the stable contract: resources live under /v1/<league>/teams/... and /v1/<league>/games/..., and every read is a GET.
+// responses only gain fields here; anything that would break a client goes in v2
+pub fn routes() -> Vec<Route> {
+    return routes![teams, injuries, box_score, previous_results, probable_lineup, ats_report, matchup, game_preview, game_odds, game_odds_history];
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams", tag = "teams",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba")),
+    responses((status = 200, description = "OK", body = Vec<Team>), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/teams")]
+pub async fn teams(league: League, _client: ApiClient) -> Result<Json<Vec<Team>>, Status> {
+    return crate::fetch_teams(league).await.map(Json);
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/injuries", tag = "injuries",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("format" = Option<String>, Query, description = "json (default), csv or xlsx; the Accept header is used when absent")),
+    responses(
+        (status = 200, description = "OK", content(
+            (Vec<TeamInjuryReport> = "application/json"),
+            (String = "text/csv"),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        )),
+        (status = 406, description = "Unsupported format"),
+        (status = 502, description = "ESPN failed")
+    )
+)]
+#[get("/<league>/injuries")]
+pub async fn injuries(league: League, format: ExportFormat, _client: ApiClient) -> Result<Exported<Vec<TeamInjuryReport>>, Status> {
+    return export::respond(format, &format!("{}-injuries", league.path()), crate::get_injuries_with_team_code(league).await?, |reports| export::injuries_table(reports));
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams/{team_code}/box-score", tag = "box scores",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("format" = Option<String>, Query, description = "json (default), csv or xlsx; the Accept header is used when absent")),
+    responses(
+        (status = 200, description = "The team's latest game", content(
+            (TeamBox = "application/json"),
+            (String = "text/csv"),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        )),
+        (status = 404, description = "Unknown team, or no completed game yet"),
+        (status = 406, description = "Unsupported format"),
+        (status = 502, description = "ESPN failed")
+    )
+)]
+#[get("/<league>/teams/<team_code>/box-score")]
+pub async fn box_score(league: League, team_code: &str, format: ExportFormat, _client: ApiClient) -> Result<Exported<TeamBox>, Status> {
+    let team_box = crate::get_team_box_score(league, team_code).await?;
+    return export::respond(format, &format!("{}-box-score", team_code), team_box, export::box_score_table);
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams/{team_code}/previous-results", tag = "teams",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("format" = Option<String>, Query, description = "json (default), csv or xlsx; the Accept header is used when absent")),
+    responses(
+        (status = 200, description = "The team's last five results", content(
+            (Vec<GameResult> = "application/json"),
+            (String = "text/csv"),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        )),
+        (status = 404, description = "Unknown team"),
+        (status = 406, description = "Unsupported format"),
+        (status = 502, description = "ESPN failed")
+    )
+)]
+#[get("/<league>/teams/<team_code>/previous-results")]
+pub async fn previous_results(league: League, team_code: String, format: ExportFormat, _client: ApiClient) -> Result<Exported<Vec<GameResult>>, Status> {
+    let game_results = crate::get_previous_results(league, team_code.to_string()).await?;
+    return export::respond(format, &format!("{}-previous-results", team_code), game_results, |game_results| export::previous_results_table(game_results));
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams/{team_code}/probable-lineup", tag = "lineups",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor")),
+    responses((status = 200, description = "Probable lineups for the team's next game", body = UpcomingLineups), (status = 404, description = "Unknown team, or no upcoming game"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/teams/<team_code>/probable-lineup")]
+pub async fn probable_lineup(league: League, team_code: String, _client: ApiClient) -> Result<Json<UpcomingLineups>, Status> {
+    return crate::get_upcoming_lineups(league, &team_code).await.map(Json);
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams/{team_code}/ats", tag = "odds",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_code" = String, Path, description = "ESPN team code, e.g. tor"), ("last" = Option<usize>, Query, description = "Games in the last_n summary, default 10")),
+    responses((status = 200, description = "OK", body = ats::AtsReport), (status = 404, description = "Unknown team"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/teams/<team_code>/ats?<last>")]
+pub async fn ats_report(league: League, team_code: &str, last: Option<usize>, store: &State<OddsHistoryStore>, _client: ApiClient) -> Result<Json<ats::AtsReport>, Status> {
+    let team_page_html = crate::get_page(league.url(&format!("team/_/name/{}", team_code))).await?;
+    let completed_games = crate::get_completed_games_from_team_page_html(&team_page_html);
+    return Ok(Json(ats::ats_report(league, team_code, &completed_games, store, last.unwrap_or(10))));
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/teams/{team_a}/matchups/{team_b}", tag = "teams",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("team_a" = String, Path), ("team_b" = String, Path), ("seasons" = Option<u32>, Query, description = "Seasons to look back, default 3, at most 5")),
+    responses((status = 200, description = "OK", body = matchups::Matchup), (status = 404, description = "Unknown team"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/teams/<team_a>/matchups/<team_b>?<seasons>")]
+pub async fn matchup(league: League, team_a: &str, team_b: &str, seasons: Option<u32>, _client: ApiClient) -> Result<Json<matchups::Matchup>, Status> {
+    return matchups::get_matchup(league, team_a, team_b, seasons.unwrap_or(3)).await.map(Json);
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/games/{game_id}/preview", tag = "games",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("game_id" = String, Path, description = "ESPN game id")),
+    responses((status = 200, description = "OK", body = preview::GamePreview), (status = 404, description = "Game not found"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/games/<game_id>/preview")]
+pub async fn game_preview(league: League, game_id: &str, _client: ApiClient) -> Result<Json<preview::GamePreview>, Status> {
+    let game_page_html = crate::get_page(league.url(&format!("game/_/gameId/{}", game_id))).await?;
+    let game = preview::get_game_info_from_html(game_id, &game_page_html).ok_or(Status::NotFound)?;
+    let injuries = crate::get_injuries_with_team_code(league).await?;
+    let away = crate::get_probable_lineup(league, &game.away_team.team_code, &injuries).await?;
+    let home = crate::get_probable_lineup(league, &game.home_team.team_code, &injuries).await?;
+    return Ok(Json(preview::GamePreview {
+        head_to_head: preview::get_season_series_from_html(&game_page_html),
+        odds: crate::get_odds_for_game_html(game_page_html),
+        game,
+        away,
+        home
+    }));
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/games/{game_id}/odds", tag = "odds",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("game_id" = String, Path, description = "ESPN game id")),
+    responses((status = 200, description = "OK", body = GameOdds), (status = 404, description = "Game not found, or no odds posted"), (status = 502, description = "ESPN failed"))
+)]
+#[get("/<league>/games/<game_id>/odds")]
+pub async fn game_odds(league: League, game_id: String, _client: ApiClient) -> Result<Json<GameOdds>, Status> {
+    return crate::find_odds_for_game(league, game_id).await?.map(Json).ok_or(Status::NotFound);
+}
+
+#[utoipa::path(
+    get, path = "/v1/{league}/games/{game_id}/odds/history", tag = "odds",
+    params(("league" = League, Path, description = "League path segment, e.g. nba or wnba"), ("game_id" = String, Path, description = "ESPN game id")),
+    responses((status = 200, description = "OK", body = OddsHistory), (status = 404, description = "No snapshots recorded"))
+)]
+#[get("/<league>/games/<game_id>/odds/history")]
+pub fn game_odds_history(league: League, game_id: &str, store: &State<OddsHistoryStore>, _client: ApiClient) -> Option<Json<OddsHistory>> {
+    return store.history(league, game_id).map(Json);
+}
@@ -0,0 +1,198 @@
+use askama::Template;
+use rocket::form::FromFormField;
+use rocket::request::Request;
+use rocket::response::{self, content, Responder, Response};
+
+use crate::{GameOdds, OrientedTeam, Player, PlayerInjury, TeamBox, TeamInjuryReport};
+use crate::league::League;
+
+#[derive(Debug, Clone, Copy, PartialEq, FromFormField)]
+pub enum Theme {
+    Light,
+    Dark
+}
+
+pub struct Palette {
+    background: &'static str,
+    foreground: &'static str,
+    muted: &'static str,
+    border: &'static str,
+    stripe: &'static str,
+    accent: String
+}
+
+fn valid_accent(accent: &str) -> bool {
+    return (accent.len() == 3 || accent.len() == 6) && accent.chars().all(|c| c.is_ascii_hexdigit());
+}
+
+// the accent is a bare hex color (e.g. `accent=ce1141`) so it can sit in a query string; anything else keeps the theme's default
+pub fn palette(theme: Option<Theme>, accent: Option<&str>) -> Palette {
+    let accent = accent.filter(|accent| valid_accent(accent)).map(|accent| format!("#{}", accent));
+    return match theme.unwrap_or(Theme::Light) {
+        Theme::Light => Palette {
+            background: "#ffffff",
+            foreground: "#1d1d1f",
+            muted: "#6e6e73",
+            border: "#d2d2d7",
+            stripe: "#f5f5f7",
+            accent: accent.unwrap_or("#c8102e".to_string())
+        },
+        Theme::Dark => Palette {
+            background: "#121212",
+            foreground: "#f5f5f7",
+            muted: "#a1a1a6",
+            border: "#3a3a3c",
+            stripe: "#1c1c1e",
+            accent: accent.unwrap_or("#ff453a".to_string())
+        }
+    };
+}
+
+// widgets are meant to be iframed from other sites. the rest of the app keeps Shield's X-Frame-Options, which browsers
+// ignore on a response with a frame-ancestors policy
+pub struct Widget(pub String);
+
+impl<'r> Responder<'r, 'static> for Widget {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        return Response::build_from(content::RawHtml(self.0).respond_to(request)?)
+            .raw_header("Content-Security-Policy", "frame-ancestors *")
+            .ok();
+    }
+}
+
+#[derive(Template)]
+#[template(path = "widgets/box_score.html")]
+struct BoxScoreWidget<'a> {
+    palette: Palette,
+    team_box: &'a TeamBox,
+    team: &'a OrientedTeam,
+    starters: Vec<&'a Player>,
+    bench: Vec<&'a Player>
+}
+
+#[derive(Template)]
+#[template(path = "widgets/injury_card.html")]
+struct InjuryCardWidget<'a> {
+    palette: Palette,
+    report: &'a TeamInjuryReport,
+    logo: String
+}
+
+struct LineupSlot {
+    position: String,
+    name: String,
+    headshot: Option<String>
+}
+
+#[derive(Template)]
+#[template(path = "widgets/lineup.html")]
+struct LineupWidget<'a> {
+    palette: Palette,
+    team: &'a OrientedTeam,
+    starters: Vec<LineupSlot>,
+    bench: Vec<LineupSlot>,
+    injuries: Vec<&'a PlayerInjury>
+}
+
+#[derive(Template)]
+#[template(path = "widgets/odds_strip.html")]
+struct OddsStripWidget<'a> {
+    palette: Palette,
+    odds: Option<&'a GameOdds>,
+    away_logo: String,
+    home_logo: String
+}
+
+// a team's box only holds its own players, so their alignment says which side of the header is theirs
+fn own_team(team_box: &TeamBox) -> &OrientedTeam {
+    return match team_box.player_records.first().map(|player| player.alignment.as_str()) {
+        Some("away") => &team_box.overview.event.away_team,
+        _ => &team_box.overview.event.home_team
+    };
+}
+
+pub fn box_score(team_box: &TeamBox, palette: Palette) -> String {
+    return BoxScoreWidget {
+        palette,
+        team_box,
+        team: own_team(team_box),
+        starters: team_box.player_records.iter().filter(|player| player.player.starter).collect(),
+        bench: team_box.player_records.iter().filter(|player| !player.player.starter).collect()
+    }.render().unwrap();
+}
+
+pub fn injury_card(report: &TeamInjuryReport, league: League, palette: Palette) -> String {
+    return InjuryCardWidget {
+        palette,
+        report,
+        logo: league.logo_url(&report.team_code)
+    }.render().unwrap();
+}
+
+fn headshot(team_box: &TeamBox, name: &str) -> Option<String> {
+    return team_box.player_records.iter()
+        .find(|player| player.player.first_initial_and_last_name == name)
+        .and_then(|player| player.player.headshots.get("w192xh192").cloned());
+}
+
+pub fn lineup(team_code: &str, team_box: &TeamBox, injuries: &[TeamInjuryReport], palette: Palette) -> String {
+    let lineup_by_position = crate::probable_lineups(&team_box.player_records);
+    let mut starters = Vec::new();
+    let mut bench = Vec::new();
+    for position in ["PG", "SG", "SF", "PF", "C"] {
+        for (i, player) in lineup_by_position.get(position).into_iter().flatten().enumerate() {
+            let name = player.player.first_initial_and_last_name.to_string();
+            let slot = LineupSlot { position: position.to_string(), headshot: headshot(team_box, &name), name };
+            match i {
+                0 => starters.push(slot),
+                _ => bench.push(slot)
+            }
+        }
+    }
+    return LineupWidget {
+        palette,
+        team: own_team(team_box),
+        starters,
+        bench,
+        injuries: injuries.iter().filter(|report| report.team_code == team_code).flat_map(|report| report.injuries.iter()).collect()
+    }.render().unwrap();
+}
+
+pub fn odds_strip(odds: Option<&GameOdds>, league: League, palette: Palette) -> String {
+    return OddsStripWidget {
+        palette,
+        away_logo: odds.map(|odds| league.logo_url(&odds.away_team)).unwrap_or_default(),
+        home_logo: odds.map(|odds| league.logo_url(&odds.home_team)).unwrap_or_default(),
+        odds
+    }.render().unwrap();
+}
+
+#[test]
+fn box_score_widget_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-home-box.html").unwrap();
+    let team_box = crate::get_latest_game_box(&contents, crate::HomeOrAway::home, League::Nba);
+    let html = box_score(&team_box, palette(Some(Theme::Dark), Some("ce1141")));
+    assert_eq!(html.contains("--accent: #ce1141"), true);
+    assert_eq!(html.contains("P. Siakam"), true);
+    assert_eq!(html.contains("DNP-COACH&#x27;S DECISION"), true);
+    assert_eq!(html.contains("<script"), false);
+}
+
+#[test]
+fn lineup_widget_test() {
+    let contents = std::fs::read_to_string("./test-data/raptors-away-box.html").unwrap();
+    let team_box = crate::get_latest_game_box(&contents, crate::HomeOrAway::away, League::Nba);
+    let html = lineup("tor", &team_box, &[], palette(None, Some("not-a-color")));
+    assert_eq!(html.contains("--accent: #c8102e"), true);
+    // askama escapes the slashes in urls, which browsers decode inside attributes
+    assert_eq!(html.matches("players&#x2f;full").count(), 5);
+    assert_eq!(html.contains("500&#x2f;tor.png"), true);
+}
+
+#[test]
+fn odds_strip_widget_test() {
+    let odds = crate::get_odds_for_game_html(std::fs::read_to_string("./test-data/game-page-for-odds.html").unwrap());
+    let html = odds_strip(odds.as_ref(), League::Nba, palette(None, None));
+    assert_eq!(html.contains("O/U 223.0"), true);
+    assert_eq!(odds_strip(None, League::Nba, palette(None, None)).contains("Odds not yet available"), true);
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::OnceLock;
+use std::thread;
+
+// ESPN-shaped paths and the saved page each one serves. the fixtures were saved on different days, so some ids
+// are wired together here: tor's latest game is served the raptors home box score, its upcoming opponent atl gets
+// a finished team page, and phx (the away side of 401360432) borrows okc's page
+const PAGES: [(&str, &str); 16] = [
+    ("/nba/teams", "teams-page.html"),
+    ("/nba/injuries", "injuries.html"),
+    ("/nba/team/_/name/tor", "raptors-team-page-upcoming-opponent.html"),
+    ("/nba/team/_/name/atl", "team-page-game-over.html"),
+    ("/nba/team/_/name/bkn", "brooklyn-home-page-for-previous-games.html"),
+    ("/nba/team/_/name/phx", "okc-home-page-upcoming-game-id.html"),
+    ("/nba/team/_/name/mil", "team-page-live-game.html"),
+    ("/nba/boxscore/_/gameId/401401141", "raptors-home-box.html"),
+    ("/nba/boxscore/_/gameId/401360609", "raptors-away-box.html"),
+    ("/nba/boxscore/_/gameId/401360612", "raptors-home-box.html"),
+    ("/nba/boxscore/_/gameId/401307733", "raptors-home-box.html"),
+    ("/nba/boxscore/_/gameId/401360614", "raptors-away-box.html"),
+    // tor's upcoming game, and a game with odds posted
+    ("/nba/game/_/gameId/401360609", "game-page-for-odds.html"),
+    ("/nba/game/_/gameId/401360432", "game-page-for-odds.html"),
+    // a game without odds yet
+    ("/nba/game/_/gameId/401360452", "game-page-for-odds-not-found.html"),
+    ("/nba/game/_/gameId/401360620", "game-page-for-odds-not-found.html")
+];
+
+// paths ESPN is down for, answered with a 503
+const FAILING: [&str; 3] = ["/nba/team/_/name/den", "/nba/game/_/gameId/401360500", "/wnba/teams"];
+
+// pages ESPN answers with a 200 and nothing in them: mil's latest game has no box score
+const EMPTY: [&str; 1] = ["/nba/boxscore/_/gameId/401360629"];
+
+// tor's schedule for every season, with its loss to bkn whose box score is served above. there's no saved schedule
+// page, so this is the markup the schedule parser reads and nothing more
+const TOR_SCHEDULE: &str = r#"<table><tbody>
+    <tr class="Table__TR"><td><span>Tue, Apr 27</span></td>
+        <td><span>vs</span><a href="/nba/team/_/name/bkn/brooklyn-nets">Brooklyn</a></td>
+        <td><span>L</span><a href="https://www.espn.com/nba/game/_/gameId/401307733">116-103</a></td></tr>
+</tbody></table>"#;
+
+fn page(path: &str) -> Option<String> {
+    let path = path.split('?').next().unwrap().trim_end_matches('/');
+    if path.starts_with("/nba/team/schedule/_/name/tor/season/") {
+        return Some(TOR_SCHEDULE.to_string());
+    }
+    let (_, file) = PAGES.iter().find(|(page_path, _)| *page_path == path)?;
+    return Some(fs::read_to_string(format!("./test-data/{}", file)).unwrap());
+}
+
+// one request per connection; `Connection: close` also keeps the shared reqwest client from pooling a connection
+// into the runtime of a test that has already finished
+fn respond(stream: TcpStream) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(read) if read > 0 && header != "\r\n" => continue,
+            _ => break
+        }
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (status, body) = match page(path) {
+        Some(body) => ("200 OK", body),
+        None if EMPTY.contains(&path.split('?').next().unwrap()) => ("200 OK", String::new()),
+        None if FAILING.contains(&path.split('?').next().unwrap()) => ("503 Service Unavailable", "<html><body>Service unavailable</body></html>".to_string()),
+        None => ("404 Not Found", "<html><body>Page not found</body></html>".to_string())
+    };
+    let mut stream = reader.into_inner();
+    let head = format!("HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body.as_bytes());
+}
+
+// starts the server on a free port the first time it's called and returns its base URL, e.g. "http://127.0.0.1:40123"
+pub fn start() -> &'static str {
+    static BASE_URL: OnceLock<String> = OnceLock::new();
+    return BASE_URL.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+                thread::spawn(move || respond(stream));
+            }
+        });
+        base_url
+    });
+}
@@ -0,0 +1,272 @@
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+// every route run against the mock ESPN server in mock_espn, through Rocket's local client
+mod mock_espn;
+
+use rocket::figment::Figment;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::{json, Value};
+
+fn figment() -> Figment {
+    let odds_history_path = std::env::temp_dir().join(format!("qr-routes-test-{}.json", std::process::id()));
+    return rocket::Config::figment()
+        .merge(("espn_base_url", mock_espn::start()))
+        .merge(("espn_api_base_url", mock_espn::start()))
+        .merge(("page_cache_enabled", false))
+        .merge(("upstream_requests_per_second", 0.0))
+        .merge(("upstream_retries", 0))
+        .merge(("odds_history_path", odds_history_path.display().to_string()));
+}
+
+// a client per test, so each one starts with full rate limit buckets
+fn client() -> Client {
+    return Client::tracked(qr::custom(figment())).unwrap();
+}
+
+fn get_json(client: &Client, uri: &str) -> Value {
+    let response = client.get(uri.to_string()).dispatch();
+    assert_eq!(response.status(), Status::Ok, "GET {}", uri);
+    return response.into_json::<Value>().unwrap();
+}
+
+#[test]
+fn teams_test() {
+    let client = client();
+    let teams = get_json(&client, "/v1/nba/teams");
+    assert_eq!(teams.as_array().unwrap().len(), 30);
+    assert_eq!(teams.as_array().unwrap().iter().any(|team| team["id"] == "tor" && team["full_name"] == "Toronto Raptors"), true);
+    assert_eq!(client.post("/teams").dispatch().into_json::<Value>().unwrap(), teams);
+    assert_eq!(client.get("/v1/cfl/teams").dispatch().status(), Status::UnprocessableEntity);
+}
+
+#[test]
+fn injuries_test() {
+    let client = client();
+    let injuries = get_json(&client, "/v1/nba/injuries");
+    assert_eq!(injuries.as_array().unwrap().is_empty(), false);
+    assert_eq!(injuries.as_array().unwrap().iter().all(|report| report["team_code"].as_str().unwrap().len() >= 2), true);
+    assert_eq!(get_json(&client, "/injuries"), injuries);
+    // the same URL answers with CSV for `Accept: text/csv`
+    let negotiated = client.get("/v1/nba/injuries").header(Header::new("Accept", "text/csv")).dispatch();
+    assert_eq!((negotiated.content_type(), negotiated.headers().get_one("Vary")), (Some(ContentType::CSV), Some("Accept")));
+    assert_eq!(client.get("/v1/nba/injuries").dispatch().headers().get_one("Vary"), Some("Accept"));
+
+    let csv = client.get("/v1/nba/injuries?format=csv").dispatch();
+    assert_eq!(csv.content_type(), Some(ContentType::CSV));
+    assert_eq!(csv.headers().get_one("Vary"), Some("Accept"));
+    assert_eq!(csv.into_string().unwrap().starts_with("team_code,"), true);
+    assert_eq!(client.get("/v1/nba/injuries?format=pdf").dispatch().status(), Status::NotAcceptable);
+}
+
+#[test]
+fn box_score_test() {
+    let client = client();
+    let team_box = get_json(&client, "/v1/nba/teams/tor/box-score");
+    assert_eq!(team_box["overview"]["share_url"], "https://www.espn.com/nba/boxscore/_/gameId/401307733");
+    assert_eq!(team_box["player_records"].as_array().unwrap().is_empty(), false);
+    assert_eq!(get_json(&client, "/nba/box/tor"), team_box);
+    // ESPN has no page for the team
+    assert_eq!(client.get("/v1/nba/teams/xyz/box-score").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/nba/box/xyz").dispatch().status(), Status::NotFound);
+    // the latest game's page has no box score in it
+    assert_eq!(client.get("/v1/nba/teams/mil/box-score").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/nba/box/mil").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn previous_results_test() {
+    let client = client();
+    let results = get_json(&client, "/v1/nba/teams/bkn/previous-results");
+    assert_eq!(results.as_array().unwrap().len(), 5);
+    assert_eq!(results[0]["game_id"], "401360612");
+    assert_eq!(results[0]["opponent"], "Jazz");
+    assert_eq!(get_json(&client, "/nba/previous-results/bkn"), results);
+}
+
+#[test]
+fn probable_lineup_test() {
+    let client = client();
+    let lineups = get_json(&client, "/v1/nba/teams/tor/probable-lineup");
+    assert_eq!(lineups["team"]["team_code"], "tor");
+    assert_eq!(lineups["opponent"]["team_code"], "atl");
+    assert_eq!(lineups["team"]["previous_results"].as_array().unwrap().len(), 5);
+    assert_eq!(lineups["odds"]["home_team"], "tor");
+    assert_eq!(get_json(&client, "/nba/upcoming-probable-lineup/tor"), lineups);
+}
+
+#[test]
+fn ats_test() {
+    let client = client();
+    let report = get_json(&client, "/v1/nba/teams/tor/ats?last=3");
+    assert_eq!(report["team_code"], "tor");
+    // no closing lines were snapshotted for tor's games, so none of them count
+    assert_eq!((&report["season"]["games"], &report["games"]), (&json!(0), &json!([])));
+    assert_eq!(get_json(&client, "/nba/teams/tor/ats?last=3"), report);
+    assert_eq!(client.get("/v1/nba/teams/xyz/ats").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn upstream_failure_test() {
+    let client = client();
+    for uri in ["/v1/nba/teams/xyz/previous-results", "/v1/nba/teams/xyz/probable-lineup", "/v1/nba/games/1/odds"] {
+        assert_eq!(client.get(uri).dispatch().status(), Status::NotFound, "GET {}", uri);
+    }
+    // mock_espn answers 503 for den's team page, game 401360500 and the WNBA teams page
+    for uri in ["/v1/wnba/teams", "/v1/nba/teams/den/box-score", "/v1/nba/teams/den/previous-results", "/v1/nba/teams/den/ats", "/v1/nba/games/401360500/preview", "/widgets/nba/box/den"] {
+        assert_eq!(client.get(uri).dispatch().status(), Status::BadGateway, "GET {}", uri);
+    }
+}
+
+#[test]
+fn matchup_test() {
+    let client = client();
+    let matchup = get_json(&client, "/v1/nba/teams/tor/matchups/bkn?seasons=2");
+    assert_eq!(matchup["seasons"].as_array().unwrap().len(), 2);
+    // every season's schedule lists the same game, which counts once
+    assert_eq!(matchup["meetings"].as_array().unwrap().len(), 1);
+    assert_eq!((&matchup["meetings"][0]["game_id"], &matchup["meetings"][0]["winner"]), (&json!("401307733"), &json!("bkn")));
+    assert_eq!((&matchup["team_a"]["home_losses"], &matchup["team_b"]["away_wins"]), (&json!(1), &json!(1)));
+    assert_eq!(get_json(&client, "/nba/matchups/tor/bkn?seasons=2"), matchup);
+    let clamped = get_json(&client, "/v1/nba/teams/tor/matchups/bkn?seasons=500");
+    assert_eq!(clamped["seasons"].as_array().unwrap().len(), 5);
+    // a fresh client, since matchups draw from the small fan-out rate limit
+    let client = self::client();
+    // ESPN has no page for either team
+    assert_eq!(client.get("/v1/nba/teams/tor/matchups/xyz").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/v1/nba/teams/xyz/matchups/tor").dispatch().status(), Status::NotFound);
+    // mock_espn answers 503 for den's team page
+    assert_eq!(client.get("/v1/nba/teams/tor/matchups/den").dispatch().status(), Status::BadGateway);
+}
+
+#[test]
+fn game_preview_test() {
+    let client = client();
+    let preview = get_json(&client, "/v1/nba/games/401360432/preview");
+    assert_eq!(preview["game"]["away_team"]["team_code"], "phx");
+    assert_eq!(preview["home"]["team_code"], "tor");
+    assert_eq!(preview["odds"]["away_spread"], "-4.0");
+    assert_eq!(preview["odds"]["consensus"]["away_spread"], -4.5);
+    assert_eq!(client.get("/v1/nba/games/1/preview").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn game_odds_test() {
+    let client = client();
+    let odds = get_json(&client, "/v1/nba/games/401360432/odds");
+    assert_eq!((&odds["away_team"], &odds["home_team"]), (&json!("phx"), &json!("tor")));
+    assert_eq!(odds["numeric"]["favourite"], "phx");
+    assert_eq!(get_json(&client, "/nba/games/401360432/odds"), odds);
+    // the game page is there but no lines are posted yet
+    assert_eq!(client.get("/v1/nba/games/401360452/odds").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/nba/games/401360452/odds").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/v1/nba/games/401360452/odds/history").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn widgets_test() {
+    let client = client();
+    for uri in ["/widgets/nba/box/tor", "/widgets/nba/injuries/tor?theme=dark", "/widgets/nba/lineup/tor", "/widgets/nba/odds/401360432?accent=ce1141"] {
+        let response = client.get(uri).dispatch();
+        assert_eq!((response.status(), response.content_type()), (Status::Ok, Some(ContentType::HTML)), "GET {}", uri);
+        assert_eq!(response.headers().get_one("Content-Security-Policy"), Some("frame-ancestors *"), "GET {}", uri);
+    }
+    // only widgets can be framed
+    let api = client.get("/v1/nba/teams").dispatch();
+    assert_eq!((api.headers().get_one("X-Frame-Options"), api.headers().get_one("Content-Security-Policy")), (Some("SAMEORIGIN"), None));
+    let no_odds = client.get("/widgets/nba/odds/401360452").dispatch();
+    assert_eq!(no_odds.status(), Status::Ok);
+    assert_eq!(no_odds.into_string().unwrap().contains("Odds not yet available"), true);
+    for uri in ["/widgets/nba/box/xyz", "/widgets/nba/injuries/xyz", "/widgets/nba/lineup/xyz", "/widgets/nba/odds/1"] {
+        assert_eq!(client.get(uri).dispatch().status(), Status::NotFound, "GET {}", uri);
+    }
+}
+
+#[test]
+fn scraper_health_test() {
+    let client = client();
+    let health = get_json(&client, "/health/scrapers?team=tor");
+    let parsers = health["parsers"].as_array().unwrap();
+    assert_eq!(parsers.is_empty(), false);
+    assert_eq!(parsers.iter().any(|parser| parser["parser"] == "teams" && parser["status"] == "pass"), true);
+    assert_eq!(client.get("/health/scrapers?league=cfl").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn admin_test() {
+    // an open API has no ops key, so nobody gets at the admin routes
+    let open = client();
+    assert_eq!(open.get("/admin/cache").dispatch().status(), Status::Forbidden);
+    assert_eq!(open.delete("/admin/cache").dispatch().status(), Status::Forbidden);
+    assert_eq!(open.get("/admin/usage").dispatch().status(), Status::Forbidden);
+
+    let figment = figment().merge(("api_keys", json!([{"name": "partner", "key": "partner-key"}, {"name": "ops", "key": "ops-key", "ops": true}])));
+    let client = Client::tracked(qr::custom(figment)).unwrap();
+    let ops = Header::new("X-Api-Key", "ops-key");
+    assert_eq!(client.get("/admin/cache").header(ops.clone()).dispatch().into_json::<Value>().unwrap(), json!([]));
+    assert_eq!(client.delete("/admin/cache").header(ops.clone()).dispatch().into_json::<Value>().unwrap(), json!({"purged": 0}));
+    assert_eq!(client.delete("/admin/cache").header(Header::new("X-Api-Key", "partner-key")).dispatch().status(), Status::Forbidden);
+    client.get("/v1/nba/teams").header(Header::new("X-Api-Key", "partner-key")).dispatch();
+    let usage = client.get("/admin/usage").header(ops).dispatch().into_json::<Value>().unwrap();
+    assert_eq!(usage["partner"]["routes"]["teams"], 1);
+}
+
+#[test]
+fn api_key_test() {
+    let figment = figment().merge(("api_keys", json!([{"name": "partner", "key": "partner-key"}])));
+    let client = Client::tracked(qr::custom(figment)).unwrap();
+    assert_eq!(client.get("/v1/nba/teams").dispatch().status(), Status::Unauthorized);
+    let response = client.get("/v1/nba/teams").header(Header::new("X-Api-Key", "partner-key")).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(client.get("/admin/usage").header(Header::new("X-Api-Key", "partner-key")).dispatch().status(), Status::Forbidden);
+    assert_eq!(client.get("/v1/nba/teams?api_key=partner-key").dispatch().status(), Status::Unauthorized);
+    // an iframe can't set headers, so widgets take the key in the query string
+    assert_eq!(client.get("/widgets/nba/box/tor").dispatch().status(), Status::Unauthorized);
+    assert_eq!(client.get("/widgets/nba/box/tor?api_key=wrong-key").dispatch().status(), Status::Unauthorized);
+    assert_eq!(client.get("/widgets/nba/box/tor?theme=dark&api_key=partner-key").dispatch().status(), Status::Ok);
+}
+
+#[test]
+fn request_id_test() {
+    let client = client();
+    let response = client.get("/openapi.json").header(Header::new("X-Request-Id", "lb-42")).dispatch();
+    assert_eq!(response.headers().get_one("X-Request-Id"), Some("lb-42"));
+    let generated = client.get("/openapi.json").dispatch();
+    assert_eq!(generated.headers().get_one("X-Request-Id").map(|id| id.len()), Some(16));
+}
+
+#[test]
+fn metrics_test() {
+    let client = client();
+    assert_eq!(client.get("/v1/nba/games/401360452/odds").dispatch().status(), Status::NotFound);
+    let response = client.get("/metrics").dispatch();
+    assert_eq!(response.content_type().map(|content_type| content_type.to_string()), Some("text/plain; version=0.0.4; charset=utf-8".to_string()));
+    let text = response.into_string().unwrap();
+    assert_eq!(text.contains("http_requests_total{method=\"GET\",route=\"game_odds\",status=\"404\"}"), true);
+    assert_eq!(text.contains("http_request_duration_seconds_bucket{route=\"game_odds\",le=\"+Inf\"}"), true);
+    assert_eq!(text.contains("espn_fetches_total{page=\"game_page\",status=\"200\"}"), true);
+    assert_eq!(text.contains("parser_outputs_total{outcome=\"empty\",parser=\"get_odds_for_game_html\"}"), true);
+}
+
+#[test]
+fn health_test() {
+    let client = client();
+    assert_eq!(get_json(&client, "/health/live"), json!({"status": "live"}));
+    // the team registry loads in the background after liftoff
+    let mut ready = client.get("/health/ready").dispatch();
+    for _ in 0..50 {
+        if ready.status() == Status::Ok {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        ready = client.get("/health/ready").dispatch();
+    }
+    assert_eq!(ready.status(), Status::Ok);
+    let report = ready.into_json::<Value>().unwrap();
+    assert_eq!((&report["ready"], &report["team_registry"]["detail"]), (&json!(true), &json!("nba: 30 teams")));
+    let version = get_json(&client, "/version");
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(version["git_commit"].as_str().unwrap().is_empty(), false);
+    assert_eq!(version["build_time"].is_string(), true);
+    assert_eq!(version["features"], json!([]));
+}